@@ -1,327 +1,944 @@
-use std::error;
-use std::fmt;
-
-use crate::node::{Ast, NodeKind};
-
-#[derive(Debug, Clone, Copy)]
-enum GeneratorErrorKind {
-    NotLeftValue,
-}
-
-#[derive(Debug, Clone, Copy)]
-pub struct GeneratorError {
-    val: GeneratorErrorKind
-}
-
-impl GeneratorError {
-    fn new(val: GeneratorErrorKind) -> Self {
-        GeneratorError {
-            val
-        }
-    }
-
-    fn not_left_value() -> Self {
-        Self::new(GeneratorErrorKind::NotLeftValue)
-    }
-}
-
-impl fmt::Display for GeneratorError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use GeneratorErrorKind::*;
-        match self.val {
-            NotLeftValue => write!(f, "Not Left Value"),
-        }
-    }
-}
-
-impl error::Error for GeneratorError {}
-
-fn gen_left_value(ast: Ast) -> Result<(), GeneratorError>{
-    match ast {
-        Ast::Ident(_, usize) => {
-            // 変数のアドレスをraxに代入
-            println!("# Adress Read start");
-            println!("  mov rax, rbp ");
-            println!("  sub rax, {}", usize * 8);
-            println!("  push rax");
-            println!("# Adress Read finish");
-            Ok(())
-        },
-        _ => Err(GeneratorError::not_left_value()),
-    }
-}
-pub fn gen(ast: Ast) -> Result<(), GeneratorError> {
-    match ast {
-        Ast::Num(num) => {
-            println!("# Num Push");
-            println!("  push {}", num);
-            Ok(())
-        },
-        Ast::Ident(_, _) => {
-            println!("# Local Variable Read start");
-            gen_left_value(ast)?;
-            // raxには変数のアドレスが格納
-            println!("  pop rax");
-            println!("  mov rax, [rax]");
-            println!("  push rax");
-            // 変数の値がstackに積まれる
-            println!("# Local Variable Read finish");
-            Ok(())
-        },
-        Ast::ReturnNode {
-            hs,
-        } => {
-            println!("# Return start");
-            gen(*hs)?;
-            // stackにexprの値が積まれている
-            println!("  pop rax");
-            println!("  mov rsp, rbp");
-            println!("  pop rbp");
-            println!("  ret");
-            println!("# Return finish");
-            Ok(())
-        },
-        Ast::BlockNode {
-            hs,
-        } => {
-            for stmt in (*hs).into_iter() {
-                gen(stmt)?;
-            }
-            Ok(())
-        }
-        Ast::AddrNode {
-            hs,
-        } => {
-            gen_left_value(*hs)?;       // 変数のアドレスがstackにpush
-            Ok(())
-        }
-        // *4はアドレス4から値を読みだすことになるので注意
-        Ast::DerefNode {
-            hs
-        } => {
-            gen(*hs)?;                  // 変数の値(addr)がstackにpush ->
-            println!("  pop rax");
-            println!("  mov rax, [rax]");
-            println!("  push rax");
-            Ok(())
-        }
-        Ast::Node {
-            node_kind,
-            lhs,
-            rhs,
-        } => {
-            match node_kind {
-                NodeKind::Substitution => {
-                    println!("# Substitution start");
-                    gen_left_value(*lhs)?;
-                    gen(*rhs)?;
-                    println!("  pop rdi");
-                    println!("  pop rax");
-                    // raxに変数のアドレス
-                    // rdiに値
-                    println!("  mov [rax], rdi");
-                    // 右辺値がstackに積まれる
-                    // println!("  push rdi");
-                    println!("# Substitution finish");
-                },
-                _ => {
-                    println!("# Arithmetic start");
-                    gen(*lhs)?;
-                    gen(*rhs)?;
-                    println!("  pop rdi");
-                    println!("  pop rax");
-                    match node_kind {
-                        NodeKind::Add => println!("  add rax, rdi"),
-                        NodeKind::Sub => println!("  sub rax, rdi"),
-                        NodeKind::Mul => println!("  imul rax, rdi"),
-                        NodeKind::Div => {
-                            println!("  cqo");
-                            println!("  idiv rdi");
-                        },
-                        NodeKind::Substitution => unreachable!(),
-                        // 比較演算子では真なら1, 偽なら0が
-                        // raxに格納されstackに積まれる
-                        NodeKind::Small => {
-                            println!("  cmp rax, rdi");
-                            println!("  setl al");
-                            println!("  movzb rax, al");
-                        },
-                        NodeKind::EqualSmall => {
-                            println!("  cmp rax, rdi");
-                            println!("  setle al");
-                            println!("  movzb rax, al");
-                        },
-                        NodeKind::Equal => {
-                            println!("  cmp rax, rdi");
-                            println!("  sete al");
-                            println!("  movzb rax, al");
-                        },
-                        NodeKind::NotEqual => {
-                            println!("  cmp rax, rdi");
-                            println!("  setne al");
-                            println!("  movzb rax, al");
-                        },
-                    }
-                    // 計算結果がstackに積まれる
-                    println!("  push rax");
-                    println!("# Arithmetic finish");
-                }
-            }
-            Ok(())
-        },
-        Ast::IfNode {
-            for_num,
-            condition,
-            stmt,
-        } => {
-            println!("# If start");
-            gen(*condition)?;
-            println!("  pop rax");      // 結果がstackに積まれている
-            println!("  cmp rax, 0");   // 偽: 0, 真: 1
-            println!("  je .Lend{}", for_num);
-            gen(*stmt)?;
-            println!(".Lend{}:", for_num);
-            println!("# If finish");
-            Ok(())
-        },
-        Ast::IfElseNode {
-            for_num,
-            condition,
-            stmt_1,
-            stmt_2,
-        } => {
-            println!("# If Else start");
-            gen(*condition)?;
-            println!("  pop rax");      // 結果がstackに積まれている
-            println!("  cmp rax, 0");   // 偽: 0, 真: 1
-            println!("  je .Lelse{}", for_num);
-            gen(*stmt_1)?;
-            println!("  jmp .Lend{}", for_num);
-            println!(".Lelse{}:", for_num);
-            gen(*stmt_2)?;
-            println!(".Lend{}:", for_num);
-            println!("# If Else finish");
-            Ok(())
-        },
-        Ast::WhileNode {
-            for_num,
-            condition,
-            stmt,
-        } => {
-            println!("# While start");
-            println!(".Lbegin{}:", for_num);
-            gen(*condition)?;
-            println!("  pop rax");      // 結果がraxに格納されている
-            println!("  cmp rax, 0");   // 偽: 0, 真: 1
-            println!("  je .Lend{}", for_num);
-            gen(*stmt)?;
-            println!("  jmp .Lbegin{}", for_num);
-            println!(".Lend{}:", for_num);
-            println!("# While finish");
-            Ok(())
-        }
-        Ast::ForNode {
-            for_num,
-            initial,
-            condition,
-            change,
-            stmt,
-        }=> {
-            // exprの結果は使用しないためpopする
-            println!("# For start");
-            if let Some(expr_first) = *initial {
-                gen(expr_first)?;
-                // println!("  pop rax");      // 結果はraxに格納されている
-            };
-            println!(".Lbegin{}:", for_num);
-            if let Some(expr_second) = *condition {
-                gen(expr_second)?;
-                println!("  pop rax");      // 結果はraxに格納されている
-                println!("  cmp rax, 0");
-            }
-            println!("  je .Lend{}", for_num);
-            gen(*stmt)?;
-            if let Some(expr_third) = *change{
-                gen(expr_third)?;
-                // println!("  pop rax");      // 結果はraxに格納されている
-            }
-            println!("  jmp .Lbegin{}", for_num);
-            println!(".Lend{}:", for_num);
-            println!("# For finish");
-            Ok(())
-        },
-        Ast::Func(ident) => {
-            println!("  call {}", ident);
-            Ok(())
-        },
-        Ast::CallFuncNode {
-            func_name,
-            hs,
-        } => {
-            for (i, num) in (*hs).into_iter().enumerate() {
-                gen(num)?;
-                match i {
-                    0 => println!("  pop rdi"),
-                    1 => println!("  pop rsi"),
-                    2 => println!("  pop rdx"),
-                    3 => println!("  pop rcx"),
-                    4 => println!("  pop r8"),
-                    5 => println!("  pop r9"),
-                    _ => unreachable!(),
-                }
-            }
-            println!("  call {}", func_name);
-            println!(" push rax");      // 関数の結果を代入する際に結果がstackに積まれている前提で行われるため
-            Ok(())
-        }
-        Ast::FuncNode {
-            argument_num,
-            local_variable_num,
-            func_name,
-            stmt_block,
-        } => {
-            println!("{}:", func_name);
-            // プロローグ
-            // 変数の個数はlocal_variable_numに格
-            println!("  push rbp");
-            println!("  mov rbp, rsp");
-            // ローカル変数の定義
-            println!("  sub rsp, {}", local_variable_num * 8);
-            // -------------
-            //    r9
-            // -------------
-            //    r8
-            // -------------
-            //    rcx
-            // -------------
-            //    rdx
-            // -------------
-            //    rsi
-            // -------------
-            //    rdi
-            // -------------   <-    今のrbp
-            //    前のrbp
-            // -------------
-            // return addrss
-            //
-            for i in 0..argument_num {
-                match i {
-                    0 => println!("  mov [rbp - 0x08], rdi"),
-                    1 => println!("  mov [rbp - 0x10], rsi"),
-                    2 => println!("  mov [rbp - 0x18], rdx"),
-                    3 => println!("  mov [rbp - 0x20], rcx"),
-                    4 => println!("  mov [rbp - 0x28], r8"),
-                    5 => println!("  mov [rbp - 0x30], r9"),
-                    _ => unreachable!(),
-                }
-            }
-            gen(*stmt_block)?;
-            // エピローグ
-            // 最後の式の値がraxに格納されており、それが返り値
-            println!("  mov rsp, rbp");
-            println!("  pop rbp");
-            println!("  ret");
-            Ok(())
-        },
-    }
-}
+use std::error;
+use std::fmt;
+
+use crate::node::{Ast, NodeKind};
+use crate::types::{ArgClass, Type};
+
+#[derive(Debug, Clone, Copy)]
+enum GeneratorErrorKind {
+    NotLeftValue,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GeneratorError {
+    val: GeneratorErrorKind
+}
+
+impl GeneratorError {
+    fn new(val: GeneratorErrorKind) -> Self {
+        GeneratorError {
+            val
+        }
+    }
+
+    fn not_left_value() -> Self {
+        Self::new(GeneratorErrorKind::NotLeftValue)
+    }
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use GeneratorErrorKind::*;
+        match self.val {
+            NotLeftValue => write!(f, "Not Left Value"),
+        }
+    }
+}
+
+impl error::Error for GeneratorError {}
+
+// 引数を渡すレジスタ(大きさ別)
+const ARG_REG8: [&str; 6] = ["dil", "sil", "dl", "cl", "r8b", "r9b"];
+const ARG_REG16: [&str; 6] = ["di", "si", "dx", "cx", "r8w", "r9w"];
+const ARG_REG32: [&str; 6] = ["edi", "esi", "edx", "ecx", "r8d", "r9d"];
+const ARG_REG64: [&str; 6] = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+// 浮動小数点数の引数を渡すレジスタ(xmm0-xmm7)の数
+const FP_MAX: usize = 8;
+
+// stackに積まれたアドレスから値を読み出す
+fn load(ty: &Type) {
+    // 配列, 構造体は先頭アドレスのまま扱う
+    if ty.is_array() || ty.is_struct() {
+        return;
+    }
+    println!("  pop rax");
+    // 8byteより小さい値は符号付きなら符号拡張, 符号なしならゼロ拡張してraxに読み込む
+    match (ty.size(), ty.is_unsigned()) {
+        // floatは下位32bitにビット列を置く
+        (4, _) if ty.is_flonum() => println!("  mov eax, dword ptr [rax]"),
+        (1, false) => println!("  movsx rax, byte ptr [rax]"),
+        (1, true) => println!("  movzx rax, byte ptr [rax]"),
+        (2, false) => println!("  movsx rax, word ptr [rax]"),
+        (2, true) => println!("  movzx rax, word ptr [rax]"),
+        (4, false) => println!("  movsxd rax, dword ptr [rax]"),
+        // 32bitレジスタへのmovは上位32bitを0にする
+        (4, true) => println!("  mov eax, dword ptr [rax]"),
+        _ => println!("  mov rax, [rax]"),
+    }
+    println!("  push rax");
+}
+
+// raxのアドレスにrdiの値を型の大きさだけ書き込む
+fn store(ty: &Type) {
+    // 構造体はrdiのアドレスから1byteずつコピーする
+    if ty.is_struct() {
+        for i in 0..ty.size() {
+            println!("  mov r8b, [rdi + {}]", i);
+            println!("  mov [rax + {}], r8b", i);
+        }
+        return;
+    }
+    match ty.size() {
+        1 => println!("  mov [rax], dil"),
+        2 => println!("  mov [rax], di"),
+        4 => println!("  mov [rax], edi"),
+        _ => println!("  mov [rax], rdi"),
+    }
+}
+
+// 浮動小数点数はraxにビット列で置く(floatは下位32bit)
+// 演算はxmm0, xmm1で行い, 命令のサフィックスはfloatならss, doubleならsd
+fn flonum_suffix(ty: &Type) -> &'static str {
+    match ty {
+        Type::Float => "ss",
+        _ => "sd",
+    }
+}
+
+// xmm0の値をraxに戻す
+fn flonum_to_rax(ty: &Type) {
+    match ty {
+        Type::Float => println!("  movd eax, xmm0"),
+        _ => println!("  movq rax, xmm0"),
+    }
+}
+
+// rax(左辺)とrdi(右辺)の浮動小数点数の演算
+// 比較はNaNを含む場合に偽となるようパリティフラグも見る
+fn flonum_arithmetic(node_kind: &NodeKind, ty: &Type) {
+    let suffix = flonum_suffix(ty);
+    println!("  movq xmm0, rax");
+    println!("  movq xmm1, rdi");
+    match node_kind {
+        NodeKind::Add => println!("  add{} xmm0, xmm1", suffix),
+        NodeKind::Sub => println!("  sub{} xmm0, xmm1", suffix),
+        NodeKind::Mul => println!("  mul{} xmm0, xmm1", suffix),
+        NodeKind::Div => println!("  div{} xmm0, xmm1", suffix),
+        // a < b は b > a として比較する
+        NodeKind::Small => {
+            println!("  ucomi{} xmm1, xmm0", suffix);
+            println!("  seta al");
+            println!("  movzb rax, al");
+            return;
+        },
+        NodeKind::EqualSmall => {
+            println!("  ucomi{} xmm1, xmm0", suffix);
+            println!("  setae al");
+            println!("  movzb rax, al");
+            return;
+        },
+        NodeKind::Equal => {
+            println!("  ucomi{} xmm0, xmm1", suffix);
+            println!("  sete al");
+            println!("  setnp dl");
+            println!("  and al, dl");
+            println!("  movzb rax, al");
+            return;
+        },
+        NodeKind::NotEqual => {
+            println!("  ucomi{} xmm0, xmm1", suffix);
+            println!("  setne al");
+            println!("  setp dl");
+            println!("  or al, dl");
+            println!("  movzb rax, al");
+            return;
+        },
+        NodeKind::ShiftLeft | NodeKind::ShiftRight | NodeKind::Substitution => unreachable!(),
+    }
+    flonum_to_rax(ty);
+}
+
+// raxの値をfromの型からtoの型に変換する
+fn cast(from: &Type, to: &Type) {
+    match (from.is_flonum(), to) {
+        // voidへのキャストは値を捨てるだけ
+        (_, Type::Void) => (),
+        // _Boolは0以外を1にする(比較演算と同じくsetccで0か1を作る)
+        (true, Type::Bool) => {
+            println!("  movq xmm0, rax");
+            println!("  xorpd xmm1, xmm1");
+            println!("  ucomi{} xmm0, xmm1", flonum_suffix(from));
+            println!("  setne al");
+            println!("  setp dl");
+            println!("  or al, dl");
+            println!("  movzb rax, al");
+        },
+        (false, Type::Bool) => {
+            println!("  cmp rax, 0");
+            println!("  setne al");
+            println!("  movzb rax, al");
+        },
+        (true, Type::Float | Type::Double) => {
+            if from != to {
+                println!("  movq xmm0, rax");
+                println!("  cvt{}2{} xmm0, xmm0", flonum_suffix(from), flonum_suffix(to));
+                flonum_to_rax(to);
+            }
+        },
+        // unsigned longの最上位bitが立っていれば, 1/2にして(最下位bitは丸めのため残す)変換し2倍する
+        (false, Type::Float | Type::Double) if from.is_unsigned() && from.size() == 8 => {
+            let suffix = flonum_suffix(to);
+            println!("  test rax, rax");
+            println!("  js 1f");
+            println!("  cvtsi2{} xmm0, rax", suffix);
+            println!("  jmp 2f");
+            println!("1:");
+            println!("  mov rdi, rax");
+            println!("  and rdi, 1");
+            println!("  shr rax, 1");
+            println!("  or rax, rdi");
+            println!("  cvtsi2{} xmm0, rax", suffix);
+            println!("  add{} xmm0, xmm0", suffix);
+            println!("2:");
+            flonum_to_rax(to);
+        },
+        // 整数はraxで符号拡張(ゼロ拡張)済みなので64bitのまま変換する
+        (false, Type::Float | Type::Double) => {
+            println!("  cvtsi2{} xmm0, rax", flonum_suffix(to));
+            flonum_to_rax(to);
+        },
+        // 0方向に切り捨ててから整数の型に揃える
+        (true, _) => {
+            println!("  movq xmm0, rax");
+            println!("  cvtt{}2si rax, xmm0", flonum_suffix(from));
+            truncate(to);
+        },
+        (false, _) => truncate(to),
+    }
+}
+
+// raxの値を型の大きさに切り詰め, 符号拡張(符号なしならゼロ拡張)する
+// 8byteの型(long, ポインタ)は何もしない
+fn truncate(ty: &Type) {
+    match (ty.size(), ty.is_unsigned()) {
+        (1, false) => println!("  movsx rax, al"),
+        (1, true) => println!("  movzx rax, al"),
+        (2, false) => println!("  movsx rax, ax"),
+        (2, true) => println!("  movzx rax, ax"),
+        (4, false) => println!("  movsxd rax, eax"),
+        (4, true) => println!("  mov eax, eax"),
+        _ => (),
+    }
+}
+
+// call時のrspは16byte境界に揃っている必要がある
+// stackの深さは静的に追跡していないため、実行時に揃えて元のrspを退避する
+// 評価済みの引数がスタックに積まれている前提で呼び出す(構造体はアドレスが積まれている)
+// r10に引数の先頭を保存し, rspを16byte境界に揃えてからスタック渡しの引数を逆順に積む
+// メモリで返す構造体は返す先のアドレスを隠れた第1引数(rdi)で渡す
+// func_nameがNoneなら引数の下に積まれた関数へのポインタをr11に入れて呼び出す
+fn call(func_name: Option<&str>, tys: &[Type], regs: &[Option<Vec<Reg>>], ret_buf: Option<usize>) {
+    let arg_num = tys.len();
+    let stack_num: usize = tys.iter().zip(regs.iter())
+        .filter(|(_, regs)| regs.is_none())
+        .map(|(ty, _)| ty.size().div_ceil(8))
+        .sum();
+    let fp = regs.iter().flatten().flatten().filter(|reg| matches!(reg, Reg::Fp(_))).count();
+    println!("  mov r10, rsp");
+    println!("  and rsp, -16");
+    if stack_num.is_multiple_of(2) {
+        println!("  sub rsp, 8");
+    }
+    println!("  push r10");
+    for (i, ty) in tys.iter().enumerate().rev().filter(|(i, _)| regs[*i].is_none()) {
+        let slot = 8 * (arg_num - 1 - i);
+        if !ty.is_struct() {
+            println!("  push qword ptr [r10 + {}]", slot);
+            continue;
+        }
+        println!("  mov rax, [r10 + {}]", slot);
+        for k in (0..ty.size().div_ceil(8)).rev() {
+            load_eightbyte("rdi", "rax", 8 * k as i64, (ty.size() - 8 * k).min(8));
+            println!("  push rdi");
+        }
+    }
+    for (i, ty) in tys.iter().enumerate() {
+        let slot = 8 * (arg_num - 1 - i);
+        match &regs[i] {
+            Some(regs) if ty.is_struct() => {
+                println!("  mov rax, [r10 + {}]", slot);
+                for (k, reg) in regs.iter().enumerate() {
+                    load_eightbyte(&reg.name(), "rax", 8 * k as i64, (ty.size() - 8 * k).min(8));
+                }
+            },
+            Some(regs) => match regs[0] {
+                Reg::Fp(fp) => println!("  movq xmm{}, qword ptr [r10 + {}]", fp, slot),
+                Reg::Gp(gp) => println!("  mov {}, [r10 + {}]", ARG_REG64[gp], slot),
+            },
+            None => (),
+        }
+    }
+    if let Some(ret_buf) = ret_buf {
+        println!("  lea rdi, [rbp - {}]", ret_buf);
+    }
+    // 可変長引数の関数のためにalに浮動小数点数の引数の数を入れる
+    println!("  mov rax, {}", fp);
+    let arg_slots = match func_name {
+        Some(func_name) => {
+            println!("  call {}", func_name);
+            arg_num
+        },
+        None => {
+            println!("  mov r11, [r10 + {}]", 8 * arg_num);
+            println!("  call r11");
+            arg_num + 1
+        },
+    };
+    if stack_num > 0 {
+        println!("  add rsp, {}", 8 * stack_num);
+    }
+    println!("  pop rsp");
+    // 引数(と関数へのポインタ)の値を捨てる
+    if arg_slots > 0 {
+        println!("  add rsp, {}", 8 * arg_slots);
+    }
+}
+
+// 引数を渡すレジスタ(番号はARG_REG64, xmmの番号)
+#[derive(Debug, Clone, Copy)]
+enum Reg {
+    Gp(usize),
+    Fp(usize),
+}
+
+impl Reg {
+    fn name(&self) -> String {
+        match self {
+            Reg::Gp(gp) => ARG_REG64[*gp].to_string(),
+            Reg::Fp(fp) => format!("xmm{}", fp),
+        }
+    }
+}
+
+// SysV x86-64の分類に従って各引数を渡すレジスタを8byteごとに決める
+// 構造体は全ての8byteがレジスタに入る場合のみレジスタで渡し, 入らなければスタック(None)で渡す
+// gpは隠れた引数で使用済みの汎用レジスタの数
+fn assign_regs(tys: &[Type], mut gp: usize) -> Vec<Option<Vec<Reg>>> {
+    let mut fp = 0;
+    tys.iter()
+        .map(|ty| {
+            let classes = ty.classify()?;
+            let need_gp = classes.iter().filter(|class| **class == ArgClass::Integer).count();
+            let need_fp = classes.len() - need_gp;
+            if gp + need_gp > ARG_REG64.len() || fp + need_fp > FP_MAX {
+                return None;
+            }
+            let regs = classes.iter()
+                .map(|class| match class {
+                    ArgClass::Integer => {
+                        gp += 1;
+                        Reg::Gp(gp - 1)
+                    },
+                    ArgClass::Sse => {
+                        fp += 1;
+                        Reg::Fp(fp - 1)
+                    },
+                })
+                .collect();
+            Some(regs)
+        })
+        .collect()
+}
+
+// 可変長引数の関数のプロローグ
+// 引数のレジスタを全て保存領域[rbp - va_area + 24]に書き込み, va_startでコピーするva_listの要素を初期化する
+// 保存領域は汎用レジスタ6個(8byteずつ), xmmレジスタ8個(16byteずつ)の順
+fn save_va_regs(va_area: usize, has_ret_buf: bool, tys: &[Type], regs: &[Option<Vec<Reg>>]) {
+    let va_area = -(va_area as i64);
+    let mut gp = if has_ret_buf { 1 } else { 0 };
+    let mut fp = 0;
+    for reg in regs.iter().flatten().flatten() {
+        match reg {
+            Reg::Gp(_) => gp += 1,
+            Reg::Fp(_) => fp += 1,
+        }
+    }
+    let stack: usize = tys.iter().zip(regs.iter())
+        .filter(|(_, regs)| regs.is_none())
+        .map(|(ty, _)| ty.size().div_ceil(8))
+        .sum();
+    // gp_offset, fp_offset, overflow_arg_area, reg_save_area
+    println!("  mov dword ptr [rbp{:+}], {}", va_area, 8 * gp);
+    println!("  mov dword ptr [rbp{:+}], {}", va_area + 4, 48 + 16 * fp);
+    println!("  lea rax, [rbp + {}]", 16 + 8 * stack);
+    println!("  mov [rbp{:+}], rax", va_area + 8);
+    println!("  lea rax, [rbp{:+}]", va_area + 24);
+    println!("  mov [rbp{:+}], rax", va_area + 16);
+    for (i, reg) in ARG_REG64.iter().enumerate() {
+        println!("  mov [rbp{:+}], {}", va_area + 24 + 8 * i as i64, reg);
+    }
+    for i in 0..FP_MAX {
+        println!("  movsd qword ptr [rbp{:+}], xmm{}", va_area + 72 + 16 * i as i64, i);
+    }
+}
+
+// 返り値の構造体の8byteごとのレジスタ(INTEGERはrax, rdx, SSEはxmm0, xmm1の順)
+fn ret_regs(classes: &[ArgClass]) -> Vec<&'static str> {
+    let (mut gp, mut fp) = (0, 0);
+    classes.iter()
+        .map(|class| match class {
+            ArgClass::Integer => {
+                gp += 1;
+                ["rax", "rdx"][gp - 1]
+            },
+            ArgClass::Sse => {
+                fp += 1;
+                ["xmm0", "xmm1"][fp - 1]
+            },
+        })
+        .collect()
+}
+
+// [addr + offset]からn byte(8byte以下)をregに読み込む
+// 構造体の末尾を越えて読まないよう, 端数は1byteずつ組み立てる
+fn load_eightbyte(reg: &str, addr: &str, offset: i64, n: usize) {
+    match n {
+        _ if reg.starts_with("xmm") && n <= 4 => println!("  movss {}, dword ptr [{}{:+}]", reg, addr, offset),
+        _ if reg.starts_with("xmm") => println!("  movsd {}, qword ptr [{}{:+}]", reg, addr, offset),
+        8 => println!("  mov {}, [{}{:+}]", reg, addr, offset),
+        _ => {
+            println!("  mov {}, 0", reg);
+            for i in (0..n as i64).rev() {
+                println!("  shl {}, 8", reg);
+                println!("  movzx r11, byte ptr [{}{:+}]", addr, offset + i);
+                println!("  or {}, r11", reg);
+            }
+        },
+    }
+}
+
+// regの下位n byte(8byte以下)を[addr + offset]に書き込む
+fn store_eightbyte(reg: &str, addr: &str, offset: i64, n: usize) {
+    match n {
+        _ if reg.starts_with("xmm") && n <= 4 => println!("  movss dword ptr [{}{:+}], {}", addr, offset, reg),
+        _ if reg.starts_with("xmm") => println!("  movsd qword ptr [{}{:+}], {}", addr, offset, reg),
+        8 => println!("  mov [{}{:+}], {}", addr, offset, reg),
+        _ => {
+            println!("  mov r11, {}", reg);
+            for i in 0..n as i64 {
+                println!("  mov [{}{:+}], r11b", addr, offset + i);
+                println!("  shr r11, 8");
+            }
+        },
+    }
+}
+
+fn gen_left_value(ast: Ast) -> Result<(), GeneratorError>{
+    match ast {
+        Ast::Ident(name, offset, _) => {
+            // 変数のアドレスをraxに代入
+            println!("# Adress Read start {}", name);
+            println!("  mov rax, rbp ");
+            println!("  sub rax, {}", offset);
+            println!("  push rax");
+            println!("# Adress Read finish");
+            Ok(())
+        },
+        // グローバル変数はRIP相対でアドレスを求める
+        // intel構文ではレジスタと同じ名前(gsなど)のシンボルを参照できないため, この命令だけAT&T構文で書く
+        Ast::GlobalIdent(name, _) => {
+            println!(".att_syntax");
+            println!("  lea {}(%rip), %rax", name);
+            println!(".intel_syntax noprefix");
+            println!("  push rax");
+            Ok(())
+        },
+        // *pの場合はpの値がアドレス
+        Ast::DerefNode {
+            hs,
+            ..
+        } => gen(*hs),
+        Ast::MemberNode {
+            offset,
+            hs,
+            ..
+        } => {
+            gen_left_value(*hs)?;
+            println!("  pop rax");
+            println!("  add rax, {}", offset);
+            println!("  push rax");
+            Ok(())
+        },
+        // 構造体を返す関数呼び出しの結果は一時領域のアドレス(f().xのため)
+        Ast::CallFuncNode { ref ty, .. } if ty.is_struct() => gen(ast),
+        _ => Err(GeneratorError::not_left_value()),
+    }
+}
+pub fn gen(ast: Ast) -> Result<(), GeneratorError> {
+    match ast {
+        // 浮動小数点数はビット列をraxに入れる
+        Ast::FNum(val, ty) => {
+            println!("# FNum Push");
+            match ty {
+                Type::Float => println!("  mov rax, {}", (val as f32).to_bits()),
+                _ => println!("  mov rax, {}", val.to_bits() as i64),
+            }
+            println!("  push rax");
+            Ok(())
+        },
+        Ast::Num(num) => {
+            println!("# Num Push");
+            // pushの即値は32bitまでなので一度raxに入れる
+            println!("  mov rax, {}", num as i64);
+            println!("  push rax");
+            Ok(())
+        },
+        Ast::Ident(_, _, _) | Ast::GlobalIdent(_, _) => {
+            println!("# Variable Read start");
+            let ty = ast.ty();
+            gen_left_value(ast)?;
+            // stackには変数のアドレスが格納
+            load(&ty);
+            // 変数の値がstackに積まれる
+            println!("# Variable Read finish");
+            Ok(())
+        },
+        Ast::ReturnNode {
+            hs,
+            ret_buf,
+        } => {
+            println!("# Return start");
+            let ty = hs.ty();
+            gen(*hs)?;
+            // stackにexprの値が積まれている
+            println!("  pop rax");
+            // 浮動小数点数はxmm0で返す
+            if ty.is_flonum() {
+                println!("  movq xmm0, rax");
+            }
+            // 構造体はメモリなら呼び出し元の領域にコピーしてそのアドレスを, それ以外はレジスタで返す
+            if ty.is_struct() {
+                match (ret_buf, ty.classify()) {
+                    (Some(ret_buf), _) => {
+                        println!("  mov rdi, rax");
+                        println!("  mov rax, [rbp - {}]", ret_buf);
+                        store(&ty);
+                    },
+                    (None, Some(classes)) => {
+                        println!("  mov r10, rax");
+                        for (k, reg) in ret_regs(&classes).into_iter().enumerate() {
+                            load_eightbyte(reg, "r10", 8 * k as i64, (ty.size() - 8 * k).min(8));
+                        }
+                    },
+                    // 返り値の型は構文解析で検査済みで, メモリで返す構造体にはret_bufがある
+                    (None, None) => unreachable!(),
+                }
+            }
+            println!("  mov rsp, rbp");
+            println!("  pop rbp");
+            println!("  ret");
+            println!("# Return finish");
+            Ok(())
+        },
+        Ast::BlockNode {
+            hs,
+        } => {
+            for stmt in hs.into_iter() {
+                gen(stmt)?;
+            }
+            Ok(())
+        }
+        Ast::AddrNode {
+            hs,
+            ..
+        } => {
+            gen_left_value(*hs)?;       // 変数のアドレスがstackにpush
+            Ok(())
+        }
+        // *4はアドレス4から値を読みだすことになるので注意
+        Ast::DerefNode {
+            ty,
+            hs,
+        } => {
+            gen(*hs)?;                  // 変数の値(addr)がstackにpush ->
+            load(&ty);
+            Ok(())
+        }
+        Ast::MemberNode { .. } => {
+            let ty = ast.ty();
+            gen_left_value(ast)?;
+            load(&ty);
+            Ok(())
+        },
+        Ast::CastNode {
+            ty,
+            hs,
+        } => {
+            let from = hs.ty();
+            gen(*hs)?;
+            println!("  pop rax");
+            cast(&from, &ty);
+            println!("  push rax");
+            Ok(())
+        },
+        Ast::Node {
+            node_kind,
+            ty,
+            lhs,
+            rhs,
+        } => {
+            match node_kind {
+                NodeKind::Substitution => {
+                    println!("# Substitution start");
+                    gen_left_value(*lhs)?;
+                    gen(*rhs)?;
+                    println!("  pop rdi");
+                    println!("  pop rax");
+                    // raxに変数のアドレス
+                    // rdiに値
+                    store(&ty);
+                    // 右辺値がstackに積まれる
+                    // println!("  push rdi");
+                    println!("# Substitution finish");
+                },
+                _ => {
+                    println!("# Arithmetic start");
+                    // どちらかが符号なしなら符号なしの命令を使う(シフトは左辺の型のみで決まる)
+                    let is_unsigned = match node_kind {
+                        NodeKind::ShiftLeft | NodeKind::ShiftRight => lhs.ty().is_unsigned(),
+                        _ => lhs.ty().is_unsigned() || rhs.ty().is_unsigned(),
+                    };
+                    // 両辺は同じ型に変換済み
+                    let op_ty = lhs.ty();
+                    gen(*lhs)?;
+                    gen(*rhs)?;
+                    println!("  pop rdi");
+                    println!("  pop rax");
+                    match node_kind {
+                        _ if op_ty.is_flonum() => flonum_arithmetic(&node_kind, &op_ty),
+                        NodeKind::Add => println!("  add rax, rdi"),
+                        NodeKind::Sub => println!("  sub rax, rdi"),
+                        NodeKind::Mul => println!("  imul rax, rdi"),
+                        NodeKind::Div if is_unsigned => {
+                            println!("  mov rdx, 0");
+                            println!("  div rdi");
+                        },
+                        NodeKind::Div => {
+                            println!("  cqo");
+                            println!("  idiv rdi");
+                        },
+                        // シフト量はclで指定する
+                        NodeKind::ShiftLeft => {
+                            println!("  mov rcx, rdi");
+                            println!("  shl rax, cl");
+                        },
+                        NodeKind::ShiftRight => {
+                            println!("  mov rcx, rdi");
+                            if is_unsigned {
+                                println!("  shr rax, cl");
+                            } else {
+                                println!("  sar rax, cl");
+                            }
+                        },
+                        NodeKind::Substitution => unreachable!(),
+                        // 比較演算子では真なら1, 偽なら0が
+                        // raxに格納されstackに積まれる
+                        NodeKind::Small => {
+                            println!("  cmp rax, rdi");
+                            if is_unsigned {
+                                println!("  setb al");
+                            } else {
+                                println!("  setl al");
+                            }
+                            println!("  movzb rax, al");
+                        },
+                        NodeKind::EqualSmall => {
+                            println!("  cmp rax, rdi");
+                            if is_unsigned {
+                                println!("  setbe al");
+                            } else {
+                                println!("  setle al");
+                            }
+                            println!("  movzb rax, al");
+                        },
+                        NodeKind::Equal => {
+                            println!("  cmp rax, rdi");
+                            println!("  sete al");
+                            println!("  movzb rax, al");
+                        },
+                        NodeKind::NotEqual => {
+                            println!("  cmp rax, rdi");
+                            println!("  setne al");
+                            println!("  movzb rax, al");
+                        },
+                    }
+                    // 64bitで計算した結果を型の大きさに揃える
+                    if ty.is_integer() {
+                        truncate(&ty);
+                    }
+                    // 計算結果がstackに積まれる
+                    println!("  push rax");
+                    println!("# Arithmetic finish");
+                }
+            }
+            Ok(())
+        },
+        Ast::IfNode {
+            for_num,
+            condition,
+            stmt,
+        } => {
+            println!("# If start");
+            gen(*condition)?;
+            println!("  pop rax");      // 結果がstackに積まれている
+            println!("  cmp rax, 0");   // 偽: 0, 真: 1
+            println!("  je .Lifend{}", for_num);
+            gen(*stmt)?;
+            println!(".Lifend{}:", for_num);
+            println!("# If finish");
+            Ok(())
+        },
+        Ast::IfElseNode {
+            for_num,
+            condition,
+            stmt_1,
+            stmt_2,
+        } => {
+            println!("# If Else start");
+            gen(*condition)?;
+            println!("  pop rax");      // 結果がstackに積まれている
+            println!("  cmp rax, 0");   // 偽: 0, 真: 1
+            println!("  je .Lelse{}", for_num);
+            gen(*stmt_1)?;
+            println!("  jmp .Lifend{}", for_num);
+            println!(".Lelse{}:", for_num);
+            gen(*stmt_2)?;
+            println!(".Lifend{}:", for_num);
+            println!("# If Else finish");
+            Ok(())
+        },
+        Ast::WhileNode {
+            for_num,
+            condition,
+            stmt,
+        } => {
+            println!("# While start");
+            println!(".Lwhilebegin{}:", for_num);
+            gen(*condition)?;
+            println!("  pop rax");      // 結果がraxに格納されている
+            println!("  cmp rax, 0");   // 偽: 0, 真: 1
+            println!("  je .Lwhileend{}", for_num);
+            gen(*stmt)?;
+            println!("  jmp .Lwhilebegin{}", for_num);
+            println!(".Lwhileend{}:", for_num);
+            println!("# While finish");
+            Ok(())
+        }
+        Ast::ForNode {
+            for_num,
+            initial,
+            condition,
+            change,
+            stmt,
+        }=> {
+            // exprの結果は使用しないためpopする
+            println!("# For start");
+            if let Some(expr_first) = *initial {
+                gen(expr_first)?;
+                // println!("  pop rax");      // 結果はraxに格納されている
+            };
+            println!(".Lforbegin{}:", for_num);
+            if let Some(expr_second) = *condition {
+                gen(expr_second)?;
+                println!("  pop rax");      // 結果はraxに格納されている
+                println!("  cmp rax, 0");
+            }
+            println!("  je .Lforend{}", for_num);
+            gen(*stmt)?;
+            if let Some(expr_third) = *change{
+                gen(expr_third)?;
+                // println!("  pop rax");      // 結果はraxに格納されている
+            }
+            println!("  jmp .Lforbegin{}", for_num);
+            println!(".Lforend{}:", for_num);
+            println!("# For finish");
+            Ok(())
+        },
+        // apの指すva_listの要素から次の引数のアドレスを求めて読み出す
+        // レジスタの保存領域を使い切っていればoverflow_arg_area(スタック)から読む
+        Ast::VaArgNode {
+            ty,
+            hs,
+        } => {
+            let (offset, limit, step) = if ty.is_flonum() { (4, 176, 16) } else { (0, 48, 8) };
+            gen(*hs)?;
+            println!("  pop rcx");
+            println!("  mov eax, dword ptr [rcx + {}]", offset);
+            println!("  cmp eax, {}", limit - step);
+            println!("  ja 1f");
+            println!("  mov rdx, [rcx + 16]");
+            println!("  add rdx, rax");
+            println!("  add eax, {}", step);
+            println!("  mov dword ptr [rcx + {}], eax", offset);
+            println!("  jmp 2f");
+            println!("1:");
+            println!("  mov rdx, [rcx + 8]");
+            println!("  lea rax, [rdx + 8]");
+            println!("  mov [rcx + 8], rax");
+            println!("2:");
+            println!("  push rdx");
+            load(&ty);
+            Ok(())
+        },
+        Ast::CallFuncNode {
+            func,
+            ty,
+            hs,
+            ret_buf,
+        } => {
+            // 関数名での呼び出しは直接callし, それ以外は関数へのポインタを引数より先に積んでおく
+            let func_name = func.func_name().map(|name| name.to_string());
+            if func_name.is_none() {
+                gen(*func)?;
+            }
+            // 引数を全て評価してからレジスタに移す
+            // (引数の評価中に関数呼び出しがあるとレジスタが壊れるため)
+            let ret_classes = ty.classify();
+            let tys: Vec<Type> = hs.iter().map(|arg| arg.ty()).collect();
+            let regs = assign_regs(&tys, if ret_classes.is_none() { 1 } else { 0 });
+            for arg in hs.into_iter() {
+                gen(arg)?;
+            }
+            call(func_name.as_deref(), &tys, &regs, ret_buf.filter(|_| ret_classes.is_none()));
+            // 構造体の返り値は一時領域に置き, そのアドレスを結果とする
+            if let (Some(ret_buf), true) = (ret_buf, ty.is_struct()) {
+                if let Some(classes) = ret_classes {
+                    for (k, reg) in ret_regs(&classes).into_iter().enumerate() {
+                        store_eightbyte(reg, "rbp", 8 * k as i64 - ret_buf as i64, (ty.size() - 8 * k).min(8));
+                    }
+                }
+                println!("  lea rax, [rbp - {}]", ret_buf);
+            }
+            // 返り値は型に合わせてraxに揃える(上位bitは不定のため)
+            if ty.is_flonum() {
+                println!("  movq rax, xmm0");
+            } else if ty.is_integer() {
+                truncate(&ty);
+            }
+            println!("  push rax");      // 関数の結果を代入する際に結果がstackに積まれている前提で行われるため
+            Ok(())
+        }
+        Ast::GlobalVarNode {
+            name,
+            ty,
+            init,
+            is_static,
+        } => {
+            // 初期値があれば.data, なければ.bssに置く
+            // staticでなければ他のファイルから参照できるようにする
+            // ラベルはレジスタと同じ名前でも定義できるよう引用符で囲む
+            if !is_static {
+                println!(".global \"{}\"", name);
+            }
+            match init {
+                Some(val) => {
+                    println!(".data");
+                    println!("  .align {}", ty.align());
+                    println!("\"{}\":", name);
+                    match ty.size() {
+                        1 => println!("  .byte {}", val),
+                        2 => println!("  .short {}", val),
+                        4 => println!("  .long {}", val),
+                        _ => println!("  .quad {}", val),
+                    }
+                },
+                None => {
+                    println!(".bss");
+                    println!("  .align {}", ty.align());
+                    println!("\"{}\":", name);
+                    println!("  .zero {}", ty.size());
+                },
+            }
+            Ok(())
+        },
+        Ast::StringLiteralNode {
+            name,
+            data,
+        } => {
+            println!(".section .rodata");
+            println!("{}:", name);
+            let bytes: Vec<String> = data.iter().map(|byte| byte.to_string()).collect();
+            println!("  .byte {}", bytes.join(", "));
+            Ok(())
+        },
+        Ast::FuncNode {
+            params,
+            stack_size,
+            func_name,
+            stmt_block,
+            ret_buf,
+            va_area,
+            is_static,
+        } => {
+            if !is_static {
+                println!(".global {}", func_name);
+            }
+            println!(".text");
+            println!("{}:", func_name);
+            // プロローグ
+            // 変数の領域の大きさはstack_sizeに格納
+            println!("  push rbp");
+            println!("  mov rbp, rsp");
+            // ローカル変数の定義
+            println!("  sub rsp, {}", stack_size);
+            // メモリで返す構造体の返す先のアドレス
+            if let Some(ret_buf) = ret_buf {
+                println!("  mov [rbp - {}], rdi", ret_buf);
+            }
+            // 引数のレジスタの値をローカル変数の領域に型の大きさで書き込む
+            // レジスタに入りきらない引数は呼び出し元のスタック[rbp + 16 + 8 * k]にある
+            let tys: Vec<Type> = params.iter().map(|param| param.ty()).collect();
+            let regs = assign_regs(&tys, if ret_buf.is_some() { 1 } else { 0 });
+            if let Some(va_area) = va_area {
+                save_va_regs(va_area, ret_buf.is_some(), &tys, &regs);
+            }
+            // スタックの引数のコピーはrdiを使うため, レジスタの引数を全て書き込んでから行う
+            let mut stack_params = Vec::new();
+            for (param, regs) in params.into_iter().zip(regs) {
+                let (offset, ty) = match param {
+                    Ast::Ident(_, offset, ty) => (offset, ty),
+                    _ => unreachable!(),
+                };
+                match regs {
+                    Some(regs) if ty.is_struct() => {
+                        for (k, reg) in regs.iter().enumerate() {
+                            store_eightbyte(&reg.name(), "rbp", 8 * k as i64 - offset as i64, (ty.size() - 8 * k).min(8));
+                        }
+                    },
+                    Some(regs) => match regs[0] {
+                        Reg::Fp(fp) => {
+                            let op = if let Type::Float = ty.unqual() { "movss" } else { "movsd" };
+                            println!("  {} [rbp - {}], xmm{}", op, offset, fp);
+                        },
+                        Reg::Gp(gp) => {
+                            let reg = match ty.size() {
+                                1 => ARG_REG8[gp],
+                                2 => ARG_REG16[gp],
+                                4 => ARG_REG32[gp],
+                                _ => ARG_REG64[gp],
+                            };
+                            println!("  mov [rbp - {}], {}", offset, reg);
+                        },
+                    },
+                    None => stack_params.push((offset, ty)),
+                }
+            }
+            // 構造体はアドレスを, それ以外は値をrdiに入れてstoreで書き込む
+            let mut stack = 0;
+            for (offset, ty) in stack_params.into_iter() {
+                let op = if ty.is_struct() { "lea" } else { "mov" };
+                println!("  {} rdi, [rbp + {}]", op, 16 + 8 * stack);
+                println!("  lea rax, [rbp - {}]", offset);
+                store(&ty);
+                stack += ty.size().div_ceil(8);
+            }
+            gen(*stmt_block)?;
+            // エピローグ
+            // 最後の式の値がraxに格納されており、それが返り値
+            println!("  mov rsp, rbp");
+            println!("  pop rbp");
+            println!("  ret");
+            Ok(())
+        },
+    }
+}
@@ -1,3 +1,4 @@
-pub mod token;
-pub mod node;
-pub mod generator;
+pub mod token;
+pub mod node;
+pub mod generator;
+pub mod types;
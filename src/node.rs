@@ -1,991 +1,2542 @@
-use std::iter::Peekable;
-use std::collections::HashMap;
-
-use crate::token::{Token, TokenKind, Location};
-use std::error;
-use std::fmt;
-
-#[derive(Debug, Clone, Copy)]
-pub enum AstErrorKind {
-    UnclosedParenth,
-    NotPatternMatching,
-    RequirSemicolon,
-    RequireComma,
-    RequireLeftParenth,
-    UndeclaredFunction,
-}
-
-#[derive(Debug)]
-pub struct AstError {
-    val: AstErrorKind,
-    pos: Location,
-    pub line_num: usize,
-}
-
-impl AstError {
-    fn new(val: AstErrorKind, pos: Location, line_num: usize) -> Self {
-        AstError {
-            val,
-            pos,
-            line_num
-        }
-    }
-
-    pub fn unclosed_parenth(pos: Location, line_num: usize) -> Self{
-        Self::new(AstErrorKind::UnclosedParenth, pos, line_num)
-    }
-
-    pub fn not_pattern_matching(pos: Location, line_num: usize) -> Self {
-        Self::new(AstErrorKind::NotPatternMatching, pos, line_num)
-    }
-
-    pub fn require_semicolon(pos: Location, line_num: usize) -> Self {
-        Self::new(AstErrorKind::RequirSemicolon, pos, line_num)
-    }
-
-    pub fn require_left_parenth(pos: Location, line_num: usize) -> Self {
-        Self::new(AstErrorKind::RequireLeftParenth, pos, line_num)
-    }
-
-    pub fn require_commma(pos: Location, line_num: usize) -> Self {
-        Self::new(AstErrorKind::RequireComma, pos, line_num)
-    }
-
-    pub fn undecrlared_function(pos: Location, line_num: usize) -> Self {
-        Self::new(AstErrorKind::UndeclaredFunction, pos, line_num)
-    }
-}
-
-impl fmt::Display for AstError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use AstErrorKind::*;
-        let space = " ".repeat(self.pos.0);
-        let hat = "^".repeat(self.pos.1 - self.pos.0 + 1);
-        match self.val {
-            UnclosedParenth => write!(f, "{}{}Unclosed Parenth", space, hat),
-            NotPatternMatching => write!(f, "{}{}Not Pattern", space, hat),
-            RequirSemicolon => write!(f, "{}{}Require Semicolon", space, hat),
-            RequireComma => write!(f, "{}{}Require Comma", space, hat),
-            RequireLeftParenth => write!(f, "{}{}Require Left Parenth", space, hat),
-            UndeclaredFunction => write!(f, "{}{}Undeclared Function", space, hat),
-        }
-    }
-}
-
-impl error::Error for AstError {}
-
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum NodeKind {
-    Add,
-    Sub,
-    Mul,
-    Div,
-    Small,      // <
-    EqualSmall, // <=
-    Equal,      // ==
-    NotEqual,   // !=
-    Substitution,   // =
-}
-
-#[derive(Debug)]
-pub enum Ast {
-    Num(u64),
-    Ident(String, usize),
-    Func(String),
-    CallFuncNode {
-        func_name: String,
-        hs: Box<Vec<Ast>>,
-    },
-    ReturnNode {
-        hs: Box<Ast>,
-    },
-    BlockNode {
-        hs: Box<Vec<Ast>>,
-    },
-    AddrNode {
-        hs: Box<Ast>,
-    },
-    DerefNode {
-        hs: Box<Ast>,
-    },
-    Node {
-        node_kind: NodeKind,
-        lhs: Box<Ast>,
-        rhs: Box<Ast>,
-    },
-    ForNode {
-        for_num: usize,
-        initial: Box<Option<Ast>>,
-        condition: Box<Option<Ast>>,
-        change: Box<Option<Ast>>,
-        stmt: Box<Ast>,
-    },
-    IfNode {
-        for_num: usize,
-        condition: Box<Ast>,
-        stmt: Box<Ast>,
-    },
-    IfElseNode {
-        for_num: usize,
-        condition: Box<Ast>,
-        stmt_1: Box<Ast>,
-        stmt_2: Box<Ast>,
-    },
-    WhileNode {
-        for_num: usize,
-        condition: Box<Ast>,
-        stmt: Box<Ast>,
-    },
-    FuncNode {
-        argument_num: usize,        // Argument
-        local_variable_num: usize,
-        func_name: String,
-        stmt_block: Box<Ast>,
-    }
-}
-
-macro_rules! match_token_ok {
-    ($token_kind:path) => {
-        Token {
-            val: $token_kind,
-            pos: _,
-            line_num: _,
-        }
-    };
-}
-
-macro_rules! match_token_num {
-    ($num:ident, $pos:ident, $line_num:ident) => {
-        Token {
-            val: TokenKind::Num($num),
-            pos: $pos,
-            line_num: $line_num
-        }
-    };
-}
-
-macro_rules! match_token_ident {
-    ($str:ident) => {
-        Token {
-            val: TokenKind::Ident($str),
-            pos: _,
-            line_num: _
-        }
-    };
-}
-
-macro_rules! match_token_nothing {
-    ($pos:ident, $line_num:ident) => {
-        Token {
-            val: _,
-            pos: $pos,
-            line_num: $line_num
-        }
-    };
-}
-
-struct ControlVal {
-    val_if_else: usize,
-    val_while: usize,
-    val_for : usize,
-}
-
-impl ControlVal {
-    fn new() -> Self {
-        ControlVal {
-            val_if_else: 0,
-            val_while: 0,
-            val_for: 0,
-        }
-    }
-
-    fn val_if_else(&mut self) -> usize {
-        let res = self.val_if_else;
-        self.val_if_else += 1;
-        res
-    }
-
-    fn val_while(&mut self) -> usize {
-        let res =  self.val_while;
-        self.val_while += 1;
-        res
-    }
-
-    fn val_for(&mut self) -> usize {
-        let res = self.val_for;
-        self.val_for += 1;
-        res
-    }
-}
-
-impl Ast {
-    fn num(num: u64) -> Self {
-        Ast::Num(num)
-    }
-
-    fn node(node_kind: NodeKind, lhs: Ast, rhs: Ast) -> Self {
-        Ast::Node {
-            node_kind,
-            lhs: Box::new(lhs),
-            rhs: Box::new(rhs),
-        }
-    }
-
-    fn return_node(hs: Ast) -> Self {
-        Ast::ReturnNode {
-            hs: Box::new(hs),
-        }
-    }
-
-
-    fn block_node(hs: Vec<Ast>) -> Self {
-        Ast::BlockNode {
-            hs: Box::new(hs),
-        }
-    }
-
-    fn addr_node(hs: Ast) -> Self {
-        Ast::AddrNode {
-            hs: Box::new(hs)
-        }
-    }
-
-    fn deref_node(hs: Ast) -> Self {
-        Ast::DerefNode {
-            hs: Box::new(hs)
-        }
-    }
-
-    fn call_func_node(func_name: String, hs: Vec<Ast>) -> Self {
-        Ast::CallFuncNode {
-            func_name,
-            hs: Box::new(hs),
-        }
-    }
-
-    fn for_node(for_num: usize, initilal: Option<Ast>, condtion: Option<Ast>, change: Option<Ast>, stmt: Ast) -> Self {
-        Ast::ForNode {
-            for_num,
-            initial: Box::new(initilal),
-            condition: Box::new(condtion),
-            change: Box::new(change),
-            stmt: Box::new(stmt),
-        }
-    }
-
-    fn if_node(for_num: usize, condtion: Ast, stmt: Ast) -> Self {
-        Ast::IfNode {
-            for_num,
-            condition: Box::new(condtion),
-            stmt: Box::new(stmt),
-        }
-    }
-
-    fn if_else_node(for_num: usize, condtion: Ast, stmt_1: Ast, stmt_2: Ast) -> Self {
-        Ast::IfElseNode {
-            for_num,
-            condition: Box::new(condtion),
-            stmt_1: Box::new(stmt_1),
-            stmt_2: Box::new(stmt_2),
-        }
-    }
-
-    fn while_node(for_num: usize, condtion: Ast, stmt: Ast) -> Self {
-        Ast::WhileNode {
-            for_num,
-            condition: Box::new(condtion),
-            stmt: Box::new(stmt),
-        }
-    }
-
-    fn func_node(argument_num: usize, local_variable_num: usize, func_name: String, stmt_block: Ast) -> Self {
-        Ast::FuncNode {
-            argument_num,
-            local_variable_num,
-            func_name,
-            stmt_block: Box::new(stmt_block),
-        }
-    }
-    // program      = func*
-    // func         = ident ( "(" ( ident ",")* ident? ")" ) "{" stmt* "}"
-    // stmt         = expr ";"
-    //              | "{" stmt* "}"
-    //              | "if" "(" expr ")" stmt ("else" stmt)?
-    //              | "while" "(" expr ")" stmt
-    //              | "for" "(" expr? ";" expr? ";" expr? ")" stmt
-    //              |return" expr ";"
-    // expr         = assign
-    // assign       = equality ("=" assign)?
-    // equality     = relational ("==" relational | "!=" relational)*
-    // relationl    = add ("<" add | ">" add | "<=" add | ">=" add)*
-    // add           = mul ("+" mul | "-" mul) *
-    // mul          = unary ("*" unary | "/" unary)*
-    // unary        = "+"? primary | "-"? primary | "*" primary | "&" primary
-    // primary      = num | ident ( "(" (unary ",")* unary? ")" )? | "(" expr ")"
-    // 本当はunaryのところは符号付数字であるが、これでも構文解析はできるためこれで行く
-    pub fn program<Tokens>(tokens: &mut Peekable<Tokens>) -> Result<Vec<Ast>, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        // 一つのfuncごとにvariable_listを持つ
-        // control_val(ラベルのための連番)はprogramで一つで問題なし
-        let mut func_list = Vec::new();
-        let mut control_val = ControlVal::new();
-        while tokens.peek().unwrap().val != TokenKind::EOF {
-            let mut variable_list: HashMap<String, usize> = HashMap::new();
-            let func = Ast::func(tokens, &mut variable_list, &mut control_val)?;
-            func_list.push(func);
-        }
-        Ok(func_list)
-    }
-
-    fn func<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>, control_val: &mut ControlVal) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        match tokens.next().unwrap() {
-            match_token_ident!(str) => {
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::LParen) => {
-                        let mut argument_num = 0;
-                        loop {
-                            match tokens.peek().unwrap() {
-                                match_token_ident!(_argument) => {
-                                    match tokens.next().unwrap() {
-                                        match_token_ident!(argument) => {
-                                            // argument_list(arugmentの参照に使用)
-                                            argument_num += 1;
-                                            // variable_list(ローカル変数の参照に使用, argumentもローカル変数として使用するため追加)
-                                            let variable_list_len = variable_list.len();
-                                            variable_list.insert(argument, variable_list_len + 1);
-
-                                            match tokens.peek().unwrap() {
-                                                match_token_ok!(TokenKind::Comma) => {
-                                                    tokens.next();
-                                                    continue;
-                                                }
-                                                match_token_ok!(TokenKind::RParen) => {
-                                                    tokens.next();
-                                                    break;
-                                                }
-                                                match_token_nothing!(pos, line_num) => return Err(AstError::not_pattern_matching(*pos, *line_num))
-                                            }
-                                        }
-                                        _ => unreachable!(),
-                                    }
-                                },
-                                _ => {
-                                    match tokens.next().unwrap() {
-                                        match_token_ok!(TokenKind::RParen) => break,
-                                        match_token_nothing!(pos, line_num) => return Err(AstError::unclosed_parenth(pos, line_num))
-                                    }
-                                }
-                            }
-                        }
-                        // func()
-                        //      ^
-                        // ここまで構文解析が完了
-                        let mut res_stmt: Vec<Ast> = Vec::new();
-                        match tokens.next().unwrap() {
-                            match_token_ok!(TokenKind::LCuryBra) => {
-                                loop {
-                                    let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                    res_stmt.push(stmt);
-                                    match tokens.peek().unwrap() {
-                                        match_token_ok!(TokenKind::RCuryBra) => {
-                                            match tokens.next().unwrap() {
-                                                match_token_ok!(TokenKind::RCuryBra) => break,
-                                                _ => unreachable!(),
-                                            }
-                                        }
-                                        _ => continue,
-                                    }
-                                    // // エラー処理 todo
-                                    // if tokens.peek().unwrap().val == TokenKind::RCuryBra {
-                                    //     match tokens.next().unwrap() {
-                                    //         match_token!(TokenKind::RCuryBra, _pos) => break,
-                                    //         _ => unreachable!(),
-                                    //     }
-                                    // }
-                                }
-                            }
-                            match_token_nothing!(pos, line_num) => return Err(AstError::require_left_parenth(pos, line_num))
-                        }
-                        Ok(Ast::func_node(argument_num, variable_list.len(), str, Ast::block_node(res_stmt)))
-                    },
-                    match_token_nothing!(pos, line_num) => Err(AstError::require_left_parenth(pos, line_num))
-                }
-            },
-            match_token_nothing!(pos, line_num) => Err(AstError::undecrlared_function(pos, line_num)),
-        }
-    }
-
-    fn stmt<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>, control_val: &mut ControlVal) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        match tokens.peek().unwrap() {
-            match_token_ok!(TokenKind::Return) => {
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::Return) => {
-                        let expr = Ast::expr(tokens, variable_list)?;
-                        match tokens.next().unwrap() {
-                            match_token_ok!(TokenKind::SemiColon) => Ok(Ast::return_node(expr)),
-                            match_token_nothing!(pos, line_num) => Err(AstError::require_semicolon(pos, line_num)),
-                        }
-                    },
-                    _ => unreachable!(),
-                }
-            },
-            match_token_ok!(TokenKind::If) => {
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::If) => {
-                        match tokens.next().unwrap() {
-                            match_token_ok!(TokenKind::LParen) => {
-                                let expr = Ast::expr(tokens, variable_list)?;
-                                match tokens.next().unwrap() {
-                                    match_token_ok!(TokenKind::RParen) => {
-                                        let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                        match tokens.peek().unwrap() {
-                                            match_token_ok!(TokenKind::Else) => {
-                                                match tokens.next().unwrap() {
-                                                    match_token_ok!(TokenKind::Else) => {
-                                                        let stmt_second = Ast::stmt(tokens, variable_list, control_val)?;
-                                                        Ok(Ast::if_else_node(control_val.val_if_else(), expr, stmt, stmt_second))
-                                                    },
-                                                    _ => unreachable!(),
-                                                }
-                                            }
-                                            _ => Ok(Ast::if_node(control_val.val_if_else(), expr, stmt))
-                                        }
-                                    },
-                                    match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
-                                }
-                            },
-                            match_token_nothing!(pos, line_num) => Err(AstError::require_left_parenth(pos, line_num)),
-                        }
-                    },
-                    _ => unreachable!(),
-                }
-            },
-            match_token_ok!(TokenKind::While) => {
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::While) => {
-                        match tokens.next().unwrap() {
-                            match_token_ok!(TokenKind::LParen) => {
-                                let expr = Ast::expr(tokens, variable_list)?;
-                                match tokens.next().unwrap() {
-                                    match_token_ok!(TokenKind::RParen) => {
-                                        let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                        Ok(Ast::while_node(control_val.val_while(), expr, stmt))
-                                    },
-                                    match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
-                                }
-                            },
-                            match_token_nothing!(pos, line_num) => Err(AstError::require_left_parenth(pos, line_num)),
-                        }
-                    },
-                    _ => unreachable!(),
-                }
-            },
-            match_token_ok!(TokenKind::For) => {
-                let for_num = control_val.val_for();
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::For) => {
-                        match tokens.next().unwrap() {
-                            match_token_ok!(TokenKind::LParen) => {
-                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                //        ^
-                                match tokens.peek().unwrap() {
-                                    match_token_ok!(TokenKind::SemiColon) => {
-                                        match tokens.next().unwrap() {
-                                            match_token_ok!(TokenKind::SemiColon) => {
-                                            // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                            //                  ^
-                                                match tokens.peek().unwrap() {
-                                                    match_token_ok!(TokenKind::SemiColon) => {
-                                                        match tokens.next().unwrap() {
-                                                            match_token_ok!(TokenKind::SemiColon) => {
-                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                //                            ^
-                                                                match tokens.peek().unwrap() {
-                                                                    match_token_ok!(TokenKind::RParen) => {
-                                                                        match tokens.next().unwrap() {
-                                                                            match_token_ok!(TokenKind::RParen) => {
-                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                                //                                      ^
-                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                                                                Ok(Ast::for_node(for_num, None, None, None, stmt))
-                                                                            },
-                                                                            _ => unreachable!(),
-                                                                        }
-                                                                    },
-                                                                    _ => {
-                                                                        let expr_third = Ast::expr(tokens, variable_list)?;
-                                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                        //                                   ^
-                                                                        match tokens.next().unwrap() {
-                                                                            match_token_ok!(TokenKind::RParen) => {
-                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                                //                                      ^
-                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                                                                Ok(Ast::for_node(for_num, None, None, Some(expr_third), stmt))
-                                                                            },
-                                                                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num))
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                            _ => unreachable!(),
-                                                        }
-                                                    }
-                                                    _ => {
-                                                        let expr_second = Ast::expr(tokens, variable_list)?;
-                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                        //                         ^
-                                                        match tokens.next().unwrap() {
-                                                            match_token_ok!(TokenKind::SemiColon) => {
-                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                //                            ^
-                                                                match tokens.peek().unwrap() {
-                                                                    match_token_ok!(TokenKind::RParen) => {
-                                                                        match tokens.next().unwrap() {
-                                                                            match_token_ok!(TokenKind::RParen) => {
-                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                                //                                      ^
-                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                                                                Ok(Ast::for_node(for_num, None, Some(expr_second), None, stmt))
-                                                                            },
-                                                                            _ => unreachable!(),
-                                                                        }
-                                                                    },
-                                                                    _ => {
-                                                                        let expr_third = Ast::expr(tokens, variable_list)?;
-                                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                        //                                   ^
-                                                                        match tokens.next().unwrap() {
-                                                                            match_token_ok!(TokenKind::RParen) => {
-                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                                //                                      ^
-                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                                                                Ok(Ast::for_node(for_num, None, Some(expr_second), Some(expr_third), stmt))
-                                                                            },
-                                                                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num))
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                            _ => unreachable!(),
-                                                        }
-                                                    }
-                                                }
-                                            },
-                                            _ => unreachable!(),
-                                        }
-                                    },
-                                    _ => {
-                                        let expr_first = Ast::expr(tokens, variable_list)?;
-                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                        //               ^
-                                        match tokens.next().unwrap() {
-                                            match_token_ok!(TokenKind::SemiColon) => {
-                                            // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                            //                  ^
-                                                match tokens.peek().unwrap() {
-                                                    match_token_ok!(TokenKind::SemiColon) => {
-                                                        match tokens.next().unwrap() {
-                                                            match_token_ok!(TokenKind::SemiColon) => {
-                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                //                            ^
-                                                                match tokens.peek().unwrap() {
-                                                                    match_token_ok!(TokenKind::RParen) => {
-                                                                        match tokens.next().unwrap() {
-                                                                            match_token_ok!(TokenKind::RParen) => {
-                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                                //                                      ^
-                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                                                                Ok(Ast::for_node(for_num, Some(expr_first), None, None, stmt))
-                                                                            },
-                                                                            _ => unreachable!(),
-                                                                        }
-                                                                    },
-                                                                    _ => {
-                                                                        let expr_third = Ast::expr(tokens, variable_list)?;
-                                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                        //                                   ^
-                                                                        match tokens.next().unwrap() {
-                                                                            match_token_ok!(TokenKind::RParen) => {
-                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                                //                                      ^
-                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                                                                Ok(Ast::for_node(for_num, Some(expr_first), None, Some(expr_third), stmt))
-                                                                            },
-                                                                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num))
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                            _ => unreachable!(),
-                                                        }
-                                                    }
-                                                    _ => {
-                                                        let expr_second = Ast::expr(tokens, variable_list)?;
-                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                        //                         ^
-                                                        match tokens.next().unwrap() {
-                                                            match_token_ok!(TokenKind::SemiColon) => {
-                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                //                            ^
-                                                                match tokens.peek().unwrap() {
-                                                                    match_token_ok!(TokenKind::RParen) => {
-                                                                        match tokens.next().unwrap() {
-                                                                            match_token_ok!(TokenKind::RParen) => {
-                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                                //                                      ^
-                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                                                                Ok(Ast::for_node(for_num, Some(expr_first), Some(expr_second), None, stmt))
-                                                                            },
-                                                                            _ => unreachable!(),
-                                                                        }
-                                                                    },
-                                                                    _ => {
-                                                                        let expr_third = Ast::expr(tokens, variable_list)?;
-                                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                        //                                   ^
-                                                                        match tokens.next().unwrap() {
-                                                                            match_token_ok!(TokenKind::RParen) => {
-                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
-                                                                                //                                      ^
-                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                                                                                Ok(Ast::for_node(for_num, Some(expr_first), Some(expr_second), Some(expr_third), stmt))
-                                                                            },
-                                                                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num))
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                            _ => unreachable!(),
-                                                        }
-                                                    }
-                                                }
-                                            },
-                                            match_token_nothing!(pos, line_num) => Err(AstError::require_semicolon(pos, line_num)),
-                                        }
-                                    }
-                                }
-                            },
-                            match_token_nothing!(pos, line_num) => Err(AstError::require_left_parenth(pos, line_num)),
-                        }
-                    },
-                    _ => unreachable!(),
-                }
-            },
-            match_token_ok!(TokenKind::LCuryBra) => {
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::LCuryBra) => {
-                        let mut res_stmt: Vec<Ast> = Vec::new();
-                        loop {
-                            let stmt = Ast::stmt(tokens, variable_list, control_val)?;
-                            res_stmt.push(stmt);
-                            match tokens.peek().unwrap() {
-                                match_token_ok!(TokenKind::RCuryBra) => {
-                                    match tokens.next().unwrap() {
-                                        match_token_ok!(TokenKind::RCuryBra) => break,
-                                        _ => unreachable!(),
-                                    }
-                                }
-                                _ => continue,
-                            }
-                            // エラー処理 todo
-                            // if tokens.peek().unwrap().val == TokenKind::RCuryBra {
-                            //     match tokens.next().unwrap() {
-                            //         match_token!(TokenKind::RCuryBra, _pos) => break,
-                            //         _ => unreachable!(),
-                            //     }
-                            // }
-                        }
-                        Ok(Ast::block_node(res_stmt))
-                    }
-                    _ => unreachable!(),
-                }
-            },
-            _ => {
-                let expr = Ast::expr(tokens, variable_list)?;
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::SemiColon) => Ok(expr),
-                    match_token_nothing!(pos, line_num) => Err(AstError::require_semicolon(pos, line_num)),
-                }
-            }
-        }
-    }
-
-    fn expr<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        Ast::assign(tokens, variable_list)
-    }
-
-    fn assign<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        let l_ast = Ast::equality(tokens, variable_list)?;
-        match tokens.peek().unwrap().val {
-            TokenKind::Substitution => {
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::Substitution) => {
-                        let r_ast = Ast::assign(tokens, variable_list)?;
-                        Ok(Ast::node(NodeKind::Substitution, l_ast, r_ast))
-                    },
-                    _ => unreachable!(),
-                }
-            }
-            _ => return Ok(l_ast)
-        }
-    }
-
-    fn equality<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        let mut l_ast = Ast::relational(tokens, variable_list)?;
-        loop {
-            match tokens.peek().unwrap() {
-                match_token_ok!(TokenKind::Equal) | match_token_ok!(TokenKind::NotEqual) => {
-                    match tokens.next().unwrap() {
-                        match_token_ok!(TokenKind::Equal) => {
-                            let r_ast = Ast::relational(tokens, variable_list)?;
-                            l_ast = Ast::node(NodeKind::Equal, l_ast, r_ast);
-                        },
-                        match_token_ok!(TokenKind::NotEqual) => {
-                            let r_ast = Ast::relational(tokens, variable_list)?;
-                            l_ast = Ast::node(NodeKind::NotEqual, l_ast, r_ast);
-                        },
-                        _ => unreachable!(),
-                    }
-                },
-                _ => return Ok(l_ast)
-            }
-        }
-    }
-
-    fn relational<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        let mut l_ast = Ast::add(tokens, variable_list)?;
-        loop {
-            match tokens.peek().unwrap() {
-                match_token_ok!(TokenKind::Small) | match_token_ok!(TokenKind::Large)
-                | match_token_ok!(TokenKind::EqualSmall) | match_token_ok!(TokenKind::EqualLarge) => {
-                    match tokens.next().unwrap() {
-                        match_token_ok!(TokenKind::Small) => {
-                            let r_ast = Ast::add(tokens, variable_list)?;
-                            l_ast = Ast::node(NodeKind::Small, l_ast, r_ast);
-                        },
-                        match_token_ok!(TokenKind::Large) => {
-                            let r_ast = Ast::add(tokens, variable_list)?;
-                            // l_ast = Ast::node(NodeKind::Large, l_ast, r_ast);
-                            l_ast = Ast::node(NodeKind::Small, r_ast, l_ast);
-                        },
-                        match_token_ok!(TokenKind::EqualSmall) => {
-                            let r_ast = Ast::add(tokens, variable_list)?;
-                            l_ast = Ast::node(NodeKind::EqualSmall, l_ast, r_ast);
-                        },
-                        match_token_ok!(TokenKind::EqualLarge) => {
-                            let r_ast = Ast::add(tokens, variable_list)?;
-                            // l_ast = Ast::node(NodeKind::EqualLarge, l_ast, r_ast);
-                            l_ast = Ast::node(NodeKind::EqualSmall, r_ast, l_ast);
-                        },
-                        _ => unreachable!(),
-                    }
-                },
-                _ => return Ok(l_ast)
-            }
-        }
-    }
-
-    fn add<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        //   mul ("+" mul | "-" mul) *
-        // ^
-        let mut l_ast = Ast::mul(tokens, variable_list)?;
-        loop {
-            //   mul ("+" mul | "-" mul) *
-            //     ^
-            match tokens.peek().unwrap() {
-                match_token_ok!(TokenKind::Plus) | match_token_ok!(TokenKind::Minus) => {
-                    //   mul ("+" mul | "-" mul) *
-                    //         ^
-                    match tokens.next().unwrap() {
-                        match_token_ok!(TokenKind::Plus) => {
-                            //   mul ("+" mul | "-" mul) *
-                            //         ^
-                            let r_ast = Ast::mul(tokens, variable_list)?;
-                            //   mul ("+" mul | "-" mul) *
-                            //              ^
-                            l_ast = Ast::node(NodeKind::Add, l_ast, r_ast);
-                        },
-                        match_token_ok!(TokenKind::Minus) => {
-                            //   mul ("+" mul | "-" mul) *
-                            //                   ^
-                            let r_ast = Ast::mul(tokens, variable_list)?;
-                            //   mul ("+" mul | "-" mul) *
-                            //                        ^
-                            l_ast = Ast::node(NodeKind::Sub, l_ast, r_ast);
-                        },
-                        _ => unreachable!(),
-                    }
-                },
-                _ => return Ok(l_ast)
-            }
-        }
-    }
-
-    fn mul<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        //   unary ("*" unary | "/" unary)*
-        //  ^
-        let mut l_ast = Ast::unary(tokens, variable_list)?;
-        loop {
-            // unary ("*" unary | "/" unary)*
-            //     ^
-            match tokens.peek().unwrap() {
-                match_token_ok!(TokenKind::Asterisk) | match_token_ok!(TokenKind::Slash) => {
-                    match tokens.next().unwrap() {
-                        match_token_ok!(TokenKind::Asterisk) => {
-                            // unary ("*" unary | "/" unary)
-                            //         ^
-                            let r_ast = Ast::unary(tokens, variable_list)?;
-                            // unary ("*" unary | "/" unary)
-                            //                ^
-                            l_ast = Ast::node(NodeKind::Mul, l_ast, r_ast);
-                        },
-                        match_token_ok!(TokenKind::Slash) => {
-                            // unary ("*" unary | "/" unary)
-                            //                     ^
-                            let r_ast = Ast::unary(tokens, variable_list)?;
-                            // unary ("*" unary | "/" unary)
-                            //                            ^
-                            l_ast = Ast::node(NodeKind::Div, l_ast, r_ast);
-                        },
-                        _ => unreachable!(),
-                    }
-                },
-                _ => return Ok(l_ast)
-            }
-        }
-    }
-
-    fn unary<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        //   ("+" | "-")? primary
-        //  ^
-        match tokens.peek().unwrap().val {
-            TokenKind::Plus | TokenKind::Minus => {
-                match tokens.next().unwrap() {
-                    //   ("+" | "-")? primary
-                    //     ^
-                    match_token_ok!(TokenKind::Plus) => return Ast::primary(tokens, variable_list),
-                    //   ("+" | "-")? primary
-                    //           ^
-                    match_token_ok!(TokenKind::Minus) => {
-                        let l_ast = Ast::num(0);
-                        let r_ast = Ast::primary(tokens, variable_list)?;
-                    //   ("+" | "-")? primary
-                    //                      ^
-                        Ok(Ast::node(NodeKind::Sub, l_ast, r_ast))
-                    },
-                    _ => unreachable!(),
-                }
-            },
-            TokenKind::Asterisk | TokenKind::Ampersand => {
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::Asterisk) => {
-                        let hs = Ast::primary(tokens, variable_list)?;
-                        Ok(Ast::deref_node(hs))
-                    },
-                    match_token_ok!(TokenKind::Ampersand) => {
-                        let hs = Ast::primary(tokens, variable_list)?;
-                        Ok(Ast::addr_node(hs))
-                    },
-                    _ => unreachable!(),
-                }
-            },
-            _ => return Ast::primary(tokens, variable_list),
-        }
-    }
-
-    fn primary<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut HashMap<String, usize>) -> Result<Ast, AstError>
-    where
-        Tokens: Iterator<Item = Token>,
-    {
-        //  num | "("
-        // ^
-        match tokens.next().unwrap() {
-            match_token_num!(num, _pos, _line_num) => Ok(Ast::num(num)),
-            match_token_ident!(str) => {
-                match tokens.peek().unwrap() {
-                    match_token_ok!(TokenKind::LParen) => {
-                        match tokens.next().unwrap() {
-                            match_token_ok!(TokenKind::LParen) => {
-                                match tokens.peek().unwrap() {
-                                    match_token_ok!(TokenKind::RParen) => {
-                                        tokens.next();
-                                        Ok(Ast::Func(str))
-                                    }
-                                    _ => {
-                                        let mut argument_list = Vec::new();
-                                        loop {
-                                            let unary = Ast::unary(tokens, variable_list)?;
-                                            argument_list.push(unary);
-                                            match tokens.next().unwrap() {
-                                                match_token_ok!(TokenKind::Comma) => continue,
-                                                match_token_ok!(TokenKind::RParen) => break,
-                                                match_token_nothing!(pos, line_num) => return Err(AstError::require_commma(pos, line_num)),
-                                            }
-                                        }
-                                        Ok(Ast::call_func_node(str, argument_list))
-                                    },
-                                }
-                            }
-                            _ => unreachable!(),
-                        }
-                    },
-                    // 変数
-                    _ => {
-                        let ident_str = str.clone();
-                        let offset = match variable_list.get(&str) {
-                            Some(&offset) => offset,
-                            None => {
-                                let variable_list_len = variable_list.len();
-                                variable_list.insert(str, variable_list_len + 1);
-                                variable_list_len + 1
-                            }
-                        };
-                        Ok(Ast::Ident(ident_str, offset))
-                    }
-                }
-            },
-            match_token_ok!(TokenKind::LParen) => {
-                // "(" epxr ")"
-                //  ^
-                let ex = Ast::expr(tokens, variable_list)?;
-                // "(" epxr ")"
-                //        ^
-                match tokens.next().unwrap() {
-                    match_token_ok!(TokenKind::RParen) => Ok(ex),
-                    match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
-                }
-            },
-            match_token_nothing!(pos, line_num) => Err(AstError::not_pattern_matching(pos, line_num)),
-        }
-    }
-}
+use std::iter::Peekable;
+use std::collections::{HashMap, HashSet};
+
+use crate::token::{Token, TokenKind, Location};
+use crate::types::{Qualifiers, StructRef, Type};
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy)]
+pub enum AstErrorKind {
+    UnclosedParenth,
+    NotPatternMatching,
+    RequirSemicolon,
+    RequireComma,
+    RequireLeftParenth,
+    UndeclaredFunction,
+    UndeclaredVariable,
+    RequireIdent,
+    NotPointer,
+    InvalidOperand,
+    UnclosedSquareBracket,
+    NotConstant,
+    NotStruct,
+    NoMember,
+    UndeclaredTag,
+    VoidValue,
+    ArgumentMismatch,
+    AssignToConst,
+    DiscardQualifiers,
+    ConflictingDeclaration,
+    ReturnMismatch,
+    IncompleteType,
+}
+
+#[derive(Debug)]
+pub struct AstError {
+    val: AstErrorKind,
+    pos: Location,
+    pub line_num: usize,
+}
+
+impl AstError {
+    fn new(val: AstErrorKind, pos: Location, line_num: usize) -> Self {
+        AstError {
+            val,
+            pos,
+            line_num
+        }
+    }
+
+    pub fn unclosed_parenth(pos: Location, line_num: usize) -> Self{
+        Self::new(AstErrorKind::UnclosedParenth, pos, line_num)
+    }
+
+    pub fn not_pattern_matching(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::NotPatternMatching, pos, line_num)
+    }
+
+    pub fn require_semicolon(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::RequirSemicolon, pos, line_num)
+    }
+
+    pub fn require_left_parenth(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::RequireLeftParenth, pos, line_num)
+    }
+
+    pub fn require_commma(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::RequireComma, pos, line_num)
+    }
+
+    pub fn undecrlared_function(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::UndeclaredFunction, pos, line_num)
+    }
+
+    pub fn undeclared_variable(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::UndeclaredVariable, pos, line_num)
+    }
+
+    pub fn require_ident(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::RequireIdent, pos, line_num)
+    }
+
+    pub fn not_pointer(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::NotPointer, pos, line_num)
+    }
+
+    pub fn invalid_operand(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::InvalidOperand, pos, line_num)
+    }
+
+    pub fn unclosed_square_bracket(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::UnclosedSquareBracket, pos, line_num)
+    }
+
+    pub fn not_constant(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::NotConstant, pos, line_num)
+    }
+
+    pub fn not_struct(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::NotStruct, pos, line_num)
+    }
+
+    pub fn no_member(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::NoMember, pos, line_num)
+    }
+
+    pub fn undeclared_tag(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::UndeclaredTag, pos, line_num)
+    }
+
+    pub fn void_value(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::VoidValue, pos, line_num)
+    }
+
+    pub fn argument_mismatch(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::ArgumentMismatch, pos, line_num)
+    }
+
+    pub fn assign_to_const(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::AssignToConst, pos, line_num)
+    }
+
+    pub fn discard_qualifiers(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::DiscardQualifiers, pos, line_num)
+    }
+
+    pub fn conflicting_declaration(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::ConflictingDeclaration, pos, line_num)
+    }
+
+    pub fn return_mismatch(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::ReturnMismatch, pos, line_num)
+    }
+
+    pub fn incomplete_type(pos: Location, line_num: usize) -> Self {
+        Self::new(AstErrorKind::IncompleteType, pos, line_num)
+    }
+}
+
+impl fmt::Display for AstError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use AstErrorKind::*;
+        let space = " ".repeat(self.pos.0);
+        let hat = "^".repeat(self.pos.1 - self.pos.0 + 1);
+        match self.val {
+            UnclosedParenth => write!(f, "{}{}Unclosed Parenth", space, hat),
+            NotPatternMatching => write!(f, "{}{}Not Pattern", space, hat),
+            RequirSemicolon => write!(f, "{}{}Require Semicolon", space, hat),
+            RequireComma => write!(f, "{}{}Require Comma", space, hat),
+            RequireLeftParenth => write!(f, "{}{}Require Left Parenth", space, hat),
+            UndeclaredFunction => write!(f, "{}{}Undeclared Function", space, hat),
+            UndeclaredVariable => write!(f, "{}{}Undeclared Variable", space, hat),
+            RequireIdent => write!(f, "{}{}Require Ident", space, hat),
+            NotPointer => write!(f, "{}{}Not Pointer", space, hat),
+            InvalidOperand => write!(f, "{}{}Invalid Operand", space, hat),
+            UnclosedSquareBracket => write!(f, "{}{}Unclosed Square Bracket", space, hat),
+            NotConstant => write!(f, "{}{}Not Constant", space, hat),
+            NotStruct => write!(f, "{}{}Not Struct", space, hat),
+            NoMember => write!(f, "{}{}No Member", space, hat),
+            UndeclaredTag => write!(f, "{}{}Undeclared Tag", space, hat),
+            VoidValue => write!(f, "{}{}Void Value", space, hat),
+            ArgumentMismatch => write!(f, "{}{}Argument Mismatch", space, hat),
+            AssignToConst => write!(f, "{}{}Assign To Const", space, hat),
+            DiscardQualifiers => write!(f, "{}{}Discard Qualifiers", space, hat),
+            ConflictingDeclaration => write!(f, "{}{}Conflicting Declaration", space, hat),
+            ReturnMismatch => write!(f, "{}{}Return Mismatch", space, hat),
+            IncompleteType => write!(f, "{}{}Incomplete Type", space, hat),
+        }
+    }
+}
+
+impl error::Error for AstError {}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NodeKind {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    ShiftLeft,  // <<
+    ShiftRight, // >>
+    Small,      // <
+    EqualSmall, // <=
+    Equal,      // ==
+    NotEqual,   // !=
+    Substitution,   // =
+}
+
+#[derive(Debug)]
+pub enum Ast {
+    Num(u64),
+    FNum(f64, Type),
+    Ident(String, usize, Type),
+    GlobalIdent(String, Type),
+    VaArgNode {
+        ty: Type,
+        hs: Box<Ast>,               // va_listの要素へのポインタ
+    },
+    CallFuncNode {
+        func: Box<Ast>,             // 関数へのポインタ
+        ty: Type,
+        hs: Vec<Ast>,
+        ret_buf: Option<usize>,     // 構造体の返り値を置く領域
+    },
+    ReturnNode {
+        hs: Box<Ast>,
+        ret_buf: Option<usize>,     // 構造体を返す先のアドレスを保存した領域
+    },
+    BlockNode {
+        hs: Vec<Ast>,
+    },
+    AddrNode {
+        ty: Type,
+        hs: Box<Ast>,
+    },
+    DerefNode {
+        ty: Type,
+        hs: Box<Ast>,
+    },
+    MemberNode {
+        ty: Type,
+        offset: usize,
+        hs: Box<Ast>,
+    },
+    CastNode {
+        ty: Type,
+        hs: Box<Ast>,
+    },
+    Node {
+        node_kind: NodeKind,
+        ty: Type,
+        lhs: Box<Ast>,
+        rhs: Box<Ast>,
+    },
+    ForNode {
+        for_num: usize,
+        initial: Box<Option<Ast>>,
+        condition: Box<Option<Ast>>,
+        change: Box<Option<Ast>>,
+        stmt: Box<Ast>,
+    },
+    IfNode {
+        for_num: usize,
+        condition: Box<Ast>,
+        stmt: Box<Ast>,
+    },
+    IfElseNode {
+        for_num: usize,
+        condition: Box<Ast>,
+        stmt_1: Box<Ast>,
+        stmt_2: Box<Ast>,
+    },
+    WhileNode {
+        for_num: usize,
+        condition: Box<Ast>,
+        stmt: Box<Ast>,
+    },
+    GlobalVarNode {
+        name: String,
+        ty: Type,
+        init: Option<i64>,
+        is_static: bool,            // 内部結合(.globalを付けない)
+    },
+    StringLiteralNode {
+        name: String,
+        data: Vec<u8>,
+    },
+    FuncNode {
+        params: Vec<Ast>,           // Argument
+        stack_size: usize,
+        func_name: String,
+        stmt_block: Box<Ast>,
+        ret_buf: Option<usize>,
+        va_area: Option<usize>,     // 可変長引数の関数のva_listの要素(直後がレジスタの保存領域)
+        is_static: bool,
+    }
+}
+
+macro_rules! match_token_ok {
+    ($token_kind:path) => {
+        Token {
+            val: $token_kind,
+            pos: _,
+            line_num: _,
+        }
+    };
+}
+
+macro_rules! match_token {
+    ($token_kind:path, $pos:ident, $line_num:ident) => {
+        Token {
+            val: $token_kind,
+            pos: $pos,
+            line_num: $line_num
+        }
+    };
+}
+
+macro_rules! match_token_num {
+    ($num:ident, $pos:ident, $line_num:ident) => {
+        Token {
+            val: TokenKind::Num($num),
+            pos: $pos,
+            line_num: $line_num
+        }
+    };
+}
+
+macro_rules! match_token_ident {
+    ($str:ident) => {
+        Token {
+            val: TokenKind::Ident($str),
+            pos: _,
+            line_num: _
+        }
+    };
+    ($str:ident, $pos:ident, $line_num:ident) => {
+        Token {
+            val: TokenKind::Ident($str),
+            pos: $pos,
+            line_num: $line_num
+        }
+    };
+}
+
+macro_rules! match_token_str {
+    ($data:ident) => {
+        Token {
+            val: TokenKind::Str($data),
+            pos: _,
+            line_num: _
+        }
+    };
+}
+
+macro_rules! match_token_nothing {
+    ($pos:ident, $line_num:ident) => {
+        Token {
+            val: _,
+            pos: $pos,
+            line_num: $line_num
+        }
+    };
+}
+
+struct Variable {
+    offset: usize,
+    ty: Type,
+}
+
+// 記憶域クラス指定子
+#[derive(Clone, Copy, PartialEq)]
+enum Storage {
+    Default,
+    Static,
+    Extern,
+}
+
+// 変数, 列挙子, typedef名は同じ名前空間に属する
+// staticなローカル変数はアセンブリ上のラベルを持つ
+enum VarScope {
+    Local(Variable),
+    Global(Type),
+    StaticLocal(String, Type),
+    EnumConstant(i64),
+    Typedef(Type),
+}
+
+// ブロックごとのスコープ
+// struct, union, enumのタグは変数とは別の名前空間
+#[derive(Default)]
+struct Scope {
+    variables: HashMap<String, VarScope>,
+    tags: HashMap<String, Type>,
+}
+
+// スコープのスタック(先頭がファイルスコープ)
+// offsetはrbpからの距離(byte単位)
+struct VariableList {
+    scopes: Vec<Scope>,
+    string_literals: Vec<Vec<u8>>,
+    globals: Vec<Ast>,
+    static_locals: Vec<Ast>,
+    internal: HashSet<String>,  // staticで宣言したファイルスコープの名前
+    defined_funcs: HashSet<String>, // 本体を定義済みの関数
+    stack_size: usize,
+    return_ty: Type,    // 解析中の関数の返り値の型
+    ret_buf: Option<usize>,
+    va_elem: Type,      // va_listの要素の型
+}
+
+impl VariableList {
+    // va_listは組み込みの型として最初から宣言しておく
+    // SysV x86-64のva_listは24byteの構造体を要素とする長さ1の配列
+    fn new() -> Self {
+        let va_elem = StructRef::new(Some("__va_elem".to_string()), false);
+        va_elem.define(vec![
+            ("gp_offset".to_string(), Type::UInt),
+            ("fp_offset".to_string(), Type::UInt),
+            ("overflow_arg_area".to_string(), Type::pointer_to(Type::Void)),
+            ("reg_save_area".to_string(), Type::pointer_to(Type::Void)),
+        ]);
+        let mut variable_list = VariableList {
+            scopes: vec![Scope::default()],
+            string_literals: Vec::new(),
+            globals: Vec::new(),
+            static_locals: Vec::new(),
+            internal: HashSet::new(),
+            defined_funcs: HashSet::new(),
+            stack_size: 0,
+            return_ty: Type::Int,
+            ret_buf: None,
+            va_elem: Type::Struct(va_elem),
+        };
+        variable_list.declare_typedef("va_list".to_string(), Type::array_of(variable_list.va_elem.clone(), 1));
+        variable_list
+    }
+
+    // 文字列リテラルは名前のないグローバル変数として扱う
+    fn add_string_literal(&mut self, mut data: Vec<u8>) -> Ast {
+        data.push(0);
+        let name = format!(".LC{}", self.string_literals.len());
+        let ty = Type::array_of(Type::Char, data.len());
+        self.string_literals.push(data);
+        Ast::GlobalIdent(name, ty)
+    }
+
+    // 同じグローバル変数の宣言(int g; int g = 1;など)は一つの定義にまとめる
+    // 初期値を二度与える, または内部結合と外部結合の定義が混在するとfalse
+    fn define_global(&mut self, name: String, ty: Type, init: Option<i64>, is_static: bool) -> bool {
+        let old = self.globals.iter_mut().find_map(|global| match global {
+            Ast::GlobalVarNode { name: old_name, init, is_static, .. } if *old_name == name => Some((init, *is_static)),
+            _ => None,
+        });
+        match old {
+            Some((Some(_), _)) if init.is_some() => return false,
+            Some((_, old_is_static)) if old_is_static != is_static => return false,
+            Some((old_init, _)) => *old_init = old_init.or(init),
+            None => self.globals.push(Ast::GlobalVarNode { name, ty, init, is_static }),
+        }
+        true
+    }
+
+    // ファイルスコープで宣言済みの名前の型
+    fn get_global(&self, name: &str) -> Option<&Type> {
+        match self.scopes[0].variables.get(name) {
+            Some(VarScope::Global(ty)) => Some(ty),
+            _ => None,
+        }
+    }
+
+    // staticなローカル変数は関数をまたいで一意な名前のグローバル変数として置く
+    fn declare_static_local(&mut self, name: String, ty: Type, init: Option<i64>) {
+        let label = format!("{}.{}", name, self.static_locals.len());
+        self.current_scope().variables.insert(name, VarScope::StaticLocal(label.clone(), ty.clone()));
+        self.static_locals.push(Ast::GlobalVarNode { name: label, ty, init, is_static: true });
+    }
+
+    // 一度staticで宣言した名前は以降の宣言でも内部結合
+    fn is_internal(&mut self, name: &str, storage: Storage) -> bool {
+        if storage == Storage::Static {
+            self.internal.insert(name.to_string());
+        }
+        self.internal.contains(name)
+    }
+
+    // 関数の開始時にスタックの領域を初期化する
+    // メモリで返す構造体は呼び出し元が渡したアドレス(rdi)を保存しておく
+    fn reset_local(&mut self, return_ty: Type) {
+        self.stack_size = 0;
+        self.ret_buf = match return_ty.classify() {
+            Some(_) => None,
+            None => Some(self.alloc_temp(&Type::Long)),
+        };
+        self.return_ty = return_ty;
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    fn leave_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn current_scope(&mut self) -> &mut Scope {
+        self.scopes.last_mut().unwrap()
+    }
+
+    fn declare_global(&mut self, name: String, ty: Type) {
+        self.current_scope().variables.insert(name, VarScope::Global(ty));
+    }
+
+    // struct, union, enumのタグ
+    fn declare_tag(&mut self, tag: String, ty: Type) {
+        self.current_scope().tags.insert(tag, ty);
+    }
+
+    fn get_tag(&self, tag: &str) -> Option<&Type> {
+        self.scopes.iter().rev().find_map(|scope| scope.tags.get(tag))
+    }
+
+    // 同じスコープで宣言済みのタグ(struct S;の後のstruct S {...}で使用)
+    fn get_current_tag(&self, tag: &str) -> Option<&Type> {
+        self.scopes.last().unwrap().tags.get(tag)
+    }
+
+    fn declare_enum_constant(&mut self, name: String, val: i64) {
+        self.current_scope().variables.insert(name, VarScope::EnumConstant(val));
+    }
+
+    fn declare_typedef(&mut self, name: String, ty: Type) {
+        self.current_scope().variables.insert(name, VarScope::Typedef(ty));
+    }
+
+    fn get_typedef(&self, name: &str) -> Option<&Type> {
+        match self.get(name) {
+            Some(VarScope::Typedef(ty)) => Some(ty),
+            _ => None,
+        }
+    }
+
+    fn declare(&mut self, name: String, ty: Type) -> usize {
+        // 変数の先頭アドレスが型のalignmentの倍数になるようにする
+        let align = ty.align();
+        self.stack_size = (self.stack_size + ty.size()).div_ceil(align) * align;
+        let offset = self.stack_size;
+        self.current_scope().variables.insert(name, VarScope::Local(Variable { offset, ty }));
+        offset
+    }
+
+    // 名前のない一時領域, 8byte単位で読み書きできるよう切り上げて確保する
+    fn alloc_temp(&mut self, ty: &Type) -> usize {
+        self.stack_size = (self.stack_size + ty.size().div_ceil(8) * 8).div_ceil(8) * 8;
+        self.stack_size
+    }
+
+    // 現在のスコープで宣言済みの名前か(内側のブロックでは同じ名前を宣言できる)
+    fn is_declared_in_current(&self, name: &str) -> bool {
+        self.scopes.last().unwrap().variables.contains_key(name)
+    }
+
+    // 内側のスコープから順に探す
+    fn get(&self, name: &str) -> Option<&VarScope> {
+        self.scopes.iter().rev().find_map(|scope| scope.variables.get(name))
+    }
+
+    // rspは16byte境界に揃える
+    fn stack_size(&self) -> usize {
+        self.stack_size.div_ceil(16) * 16
+    }
+}
+
+struct ControlVal {
+    val_if_else: usize,
+    val_while: usize,
+    val_for : usize,
+}
+
+impl ControlVal {
+    fn new() -> Self {
+        ControlVal {
+            val_if_else: 0,
+            val_while: 0,
+            val_for: 0,
+        }
+    }
+
+    fn val_if_else(&mut self) -> usize {
+        let res = self.val_if_else;
+        self.val_if_else += 1;
+        res
+    }
+
+    fn val_while(&mut self) -> usize {
+        let res =  self.val_while;
+        self.val_while += 1;
+        res
+    }
+
+    fn val_for(&mut self) -> usize {
+        let res = self.val_for;
+        self.val_for += 1;
+        res
+    }
+}
+
+impl Ast {
+    fn num(num: u64) -> Self {
+        Ast::Num(num)
+    }
+
+    // 型が異なる場合のみ変換する
+    // 変換の結果は値なので修飾は付けない
+    fn cast_node(ty: Type, hs: Ast) -> Self {
+        let ty = ty.unqual().clone();
+        if hs.ty() == ty {
+            return hs;
+        }
+        Ast::CastNode {
+            ty,
+            hs: Box::new(hs),
+        }
+    }
+
+    // 両辺が整数の二項演算は, 暗黙の型変換で両辺を共通の型に揃えてから計算する
+    fn node(node_kind: NodeKind, lhs: Ast, rhs: Ast) -> Self {
+        let (l_ty, r_ty) = (lhs.ty(), rhs.ty());
+        let is_numeric = l_ty.is_numeric() && r_ty.is_numeric();
+        let (ty, lhs, rhs) = match node_kind {
+            // 右辺を左辺の型に変換して代入する(_Boolへはポインタも代入できる)
+            NodeKind::Substitution if l_ty.is_numeric() => (l_ty.clone(), lhs, Ast::cast_node(l_ty, rhs)),
+            NodeKind::Substitution => (l_ty, lhs, rhs),
+            // シフトは両辺をそれぞれ整数拡張し, 結果は左辺の型
+            NodeKind::ShiftLeft | NodeKind::ShiftRight => {
+                let (l_ty, r_ty) = (l_ty.promote(), r_ty.promote());
+                (l_ty.clone(), Ast::cast_node(l_ty, lhs), Ast::cast_node(r_ty, rhs))
+            },
+            _ if is_numeric => {
+                let ty = Type::common_type(l_ty, r_ty);
+                let (lhs, rhs) = (Ast::cast_node(ty.clone(), lhs), Ast::cast_node(ty.clone(), rhs));
+                match node_kind {
+                    NodeKind::Add | NodeKind::Sub | NodeKind::Mul | NodeKind::Div => (ty, lhs, rhs),
+                    // 比較の結果はint
+                    _ => (Type::Int, lhs, rhs),
+                }
+            },
+            // ポインタ演算はadd_node, sub_nodeで変換済み
+            NodeKind::Add | NodeKind::Sub => (l_ty.decay(), lhs, rhs),
+            _ => (Type::Int, lhs, rhs),
+        };
+        Ast::Node {
+            node_kind,
+            ty,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        }
+    }
+
+    // ptr + numはnumをptrの指す型のサイズ倍する
+    fn add_node(lhs: Ast, rhs: Ast, pos: Location, line_num: usize) -> Result<Self, AstError> {
+        let lhs = Ast::value(lhs, pos, line_num)?;
+        let rhs = Ast::value(rhs, pos, line_num)?;
+        match (lhs.ty().base(), rhs.ty().base()) {
+            (None, None) if lhs.ty().is_numeric() && rhs.ty().is_numeric() => Ok(Ast::node(NodeKind::Add, lhs, rhs)),
+            (Some(base), None) if rhs.ty().is_integer() => {
+                let size = Ast::num(base.size() as u64);
+                Ok(Ast::node(NodeKind::Add, lhs, Ast::node(NodeKind::Mul, rhs, size)))
+            },
+            // num + ptr は ptr + num に入れ替える
+            (None, Some(_)) => Ast::add_node(rhs, lhs, pos, line_num),
+            _ => Err(AstError::invalid_operand(pos, line_num)),
+        }
+    }
+
+    // ptr - ptrは間の要素数になる
+    fn sub_node(lhs: Ast, rhs: Ast, pos: Location, line_num: usize) -> Result<Self, AstError> {
+        let lhs = Ast::value(lhs, pos, line_num)?;
+        let rhs = Ast::value(rhs, pos, line_num)?;
+        match (lhs.ty().base(), rhs.ty().base()) {
+            (None, None) if lhs.ty().is_numeric() && rhs.ty().is_numeric() => Ok(Ast::node(NodeKind::Sub, lhs, rhs)),
+            (Some(base), None) if rhs.ty().is_integer() => {
+                let size = Ast::num(base.size() as u64);
+                Ok(Ast::node(NodeKind::Sub, lhs, Ast::node(NodeKind::Mul, rhs, size)))
+            },
+            (Some(l_base), Some(r_base)) if l_base.unqual() == r_base.unqual() => {
+                let size = Ast::num(l_base.size() as u64);
+                // ポインタの差はlong
+                let diff = Ast::cast_node(Type::Long, Ast::node(NodeKind::Sub, lhs, rhs));
+                Ok(Ast::node(NodeKind::Div, diff, size))
+            },
+            _ => Err(AstError::invalid_operand(pos, line_num)),
+        }
+    }
+
+    fn return_node(hs: Ast, ret_buf: Option<usize>) -> Self {
+        Ast::ReturnNode {
+            hs: Box::new(hs),
+            ret_buf,
+        }
+    }
+
+
+    fn block_node(hs: Vec<Ast>) -> Self {
+        Ast::BlockNode {
+            hs,
+        }
+    }
+
+    // constなオブジェクトのアドレスはconstを指すポインタ
+    fn addr_node(hs: Ast) -> Self {
+        Ast::AddrNode {
+            ty: Type::pointer_to(hs.object_ty().cloned().unwrap_or_else(|| hs.ty())),
+            hs: Box::new(hs)
+        }
+    }
+
+    // 関数名は関数へのポインタとして扱う(&fとfは同じ)
+    fn func_designator(name: String, ty: Type) -> Self {
+        Ast::addr_node(Ast::GlobalIdent(name, ty))
+    }
+
+    // 関数名なら名前を返す
+    pub fn func_name(&self) -> Option<&str> {
+        match self {
+            Ast::AddrNode { hs, .. } => match &**hs {
+                Ast::GlobalIdent(name, Type::Func(_)) => Some(name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // hsがポインタであることは呼び出し側で確認する
+    fn deref_node(hs: Ast) -> Self {
+        Ast::DerefNode {
+            ty: hs.ty().base().unwrap().clone(),
+            hs: Box::new(hs)
+        }
+    }
+
+    fn call_func_node(func: Ast, ty: Type, hs: Vec<Ast>, ret_buf: Option<usize>) -> Self {
+        Ast::CallFuncNode {
+            func: Box::new(func),
+            ty,
+            hs,
+            ret_buf,
+        }
+    }
+
+    // void型の式は値として使えない
+    fn value(hs: Ast, pos: Location, line_num: usize) -> Result<Self, AstError> {
+        match hs.ty() {
+            Type::Void => Err(AstError::void_value(pos, line_num)),
+            _ => Ok(hs),
+        }
+    }
+
+    // 意味のない組み合わせの演算(ポインタの乗算, 構造体の比較など)はエラー
+    // 加減算はadd_node, sub_nodeで検査する
+    fn binary_node(node_kind: NodeKind, lhs: Ast, rhs: Ast, pos: Location, line_num: usize) -> Result<Self, AstError> {
+        let lhs = Ast::value(lhs, pos, line_num)?;
+        let rhs = Ast::value(rhs, pos, line_num)?;
+        let (l_ty, r_ty) = (lhs.ty(), rhs.ty());
+        let is_numeric = l_ty.is_numeric() && r_ty.is_numeric();
+        let is_valid = match node_kind {
+            NodeKind::Mul | NodeKind::Div => is_numeric,
+            NodeKind::ShiftLeft | NodeKind::ShiftRight => l_ty.is_integer() && r_ty.is_integer(),
+            // ポインタ同士は大小を比較できる
+            NodeKind::Small | NodeKind::EqualSmall => is_numeric || (l_ty.base().is_some() && r_ty.base().is_some()),
+            NodeKind::Equal | NodeKind::NotEqual => !l_ty.is_struct() && !r_ty.is_struct(),
+            NodeKind::Substitution => Ast::is_assignable(&l_ty, &rhs),
+            NodeKind::Add | NodeKind::Sub => true,
+        };
+        if !is_valid {
+            return Err(AstError::invalid_operand(pos, line_num));
+        }
+        if node_kind == NodeKind::Substitution && lhs.ty().discards_qualifiers(&rhs.ty()) {
+            return Err(AstError::discard_qualifiers(pos, line_num));
+        }
+        Ok(Ast::node(node_kind, lhs, rhs))
+    }
+
+    // tyの変数に代入できる右辺か
+    // ポインタへは0を, _Boolへはポインタも代入できる
+    fn is_assignable(ty: &Type, rhs: &Ast) -> bool {
+        let r_ty = rhs.ty();
+        match ty {
+            Type::Bool => r_ty.is_numeric() || r_ty.base().is_some(),
+            ty if ty.is_numeric() => r_ty.is_numeric(),
+            Type::Ptr(_) => r_ty.base().is_some() || rhs.eval() == Some(0),
+            ty => *ty == r_ty,
+        }
+    }
+
+    fn for_node(for_num: usize, initilal: Option<Ast>, condtion: Option<Ast>, change: Option<Ast>, stmt: Ast) -> Self {
+        Ast::ForNode {
+            for_num,
+            initial: Box::new(initilal),
+            condition: Box::new(condtion),
+            change: Box::new(change),
+            stmt: Box::new(stmt),
+        }
+    }
+
+    fn if_node(for_num: usize, condtion: Ast, stmt: Ast) -> Self {
+        Ast::IfNode {
+            for_num,
+            condition: Box::new(condtion),
+            stmt: Box::new(stmt),
+        }
+    }
+
+    fn if_else_node(for_num: usize, condtion: Ast, stmt_1: Ast, stmt_2: Ast) -> Self {
+        Ast::IfElseNode {
+            for_num,
+            condition: Box::new(condtion),
+            stmt_1: Box::new(stmt_1),
+            stmt_2: Box::new(stmt_2),
+        }
+    }
+
+    fn while_node(for_num: usize, condtion: Ast, stmt: Ast) -> Self {
+        Ast::WhileNode {
+            for_num,
+            condition: Box::new(condtion),
+            stmt: Box::new(stmt),
+        }
+    }
+
+    fn func_node(params: Vec<Ast>, stack_size: usize, func_name: String, stmt_block: Ast, ret_buf: Option<usize>, va_area: Option<usize>, is_static: bool) -> Self {
+        Ast::FuncNode {
+            params,
+            stack_size,
+            func_name,
+            stmt_block: Box::new(stmt_block),
+            ret_buf,
+            va_area,
+            is_static,
+        }
+    }
+    // 式の型(値として読み出すため, 変数などの修飾は含めない)
+    pub fn ty(&self) -> Type {
+        match self {
+            // intに収まらない整数定数はlong
+            Ast::Num(num) if *num as i32 as i64 != *num as i64 => Type::Long,
+            Ast::Num(_) => Type::Int,
+            Ast::FNum(_, ty) => ty.clone(),
+            Ast::Ident(_, _, ty) => ty.unqual().clone(),
+            Ast::GlobalIdent(_, ty) => ty.unqual().clone(),
+            Ast::AddrNode { ty, .. } => ty.clone(),
+            Ast::DerefNode { ty, .. } => ty.unqual().clone(),
+            Ast::MemberNode { ty, .. } => ty.unqual().clone(),
+            Ast::CastNode { ty, .. } => ty.clone(),
+            Ast::Node { ty, .. } => ty.clone(),
+            Ast::CallFuncNode { ty, .. } => ty.clone(),
+            Ast::VaArgNode { ty, .. } => ty.clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    // 左辺値が指すオブジェクトの宣言された型(修飾を含む)
+    fn object_ty(&self) -> Option<&Type> {
+        match self {
+            Ast::Ident(_, _, ty) | Ast::GlobalIdent(_, ty) | Ast::DerefNode { ty, .. } | Ast::MemberNode { ty, .. } => Some(ty),
+            _ => None,
+        }
+    }
+
+    // 定数式の評価(グローバル変数の初期化に使用)
+    fn eval(&self) -> Option<i64> {
+        match self {
+            Ast::Num(num) => Some(*num as i64),
+            // 浮動小数点数の値はeval_doubleで評価する
+            Ast::CastNode { ty, .. } if ty.is_flonum() => None,
+            // 浮動小数点数から整数への変換は0方向に切り捨てる
+            Ast::CastNode { ty, hs } if hs.ty().is_flonum() => Some(Ast::truncate(ty, hs.eval_double()? as i64)),
+            Ast::CastNode { ty, hs } => Some(Ast::truncate(ty, hs.eval()?)),
+            Ast::Node { node_kind, ty, lhs, rhs } => {
+                // シフトの符号は左辺の型で決まる
+                let is_unsigned = match node_kind {
+                    NodeKind::ShiftLeft | NodeKind::ShiftRight => lhs.ty().is_unsigned(),
+                    _ => lhs.ty().is_unsigned() || rhs.ty().is_unsigned(),
+                };
+                let (l, r) = (lhs.eval()?, rhs.eval()?);
+                let val = match node_kind {
+                    NodeKind::Add => Some(l.wrapping_add(r)),
+                    NodeKind::Sub => Some(l.wrapping_sub(r)),
+                    NodeKind::Mul => Some(l.wrapping_mul(r)),
+                    NodeKind::Div if is_unsigned => (l as u64).checked_div(r as u64).map(|val| val as i64),
+                    NodeKind::Div => l.checked_div(r),
+                    NodeKind::ShiftLeft => Some(l.wrapping_shl(r as u32)),
+                    NodeKind::ShiftRight if is_unsigned => Some((l as u64).wrapping_shr(r as u32) as i64),
+                    NodeKind::ShiftRight => Some(l.wrapping_shr(r as u32)),
+                    NodeKind::Small if is_unsigned => Some(((l as u64) < (r as u64)) as i64),
+                    NodeKind::EqualSmall if is_unsigned => Some(((l as u64) <= (r as u64)) as i64),
+                    NodeKind::Small => Some((l < r) as i64),
+                    NodeKind::EqualSmall => Some((l <= r) as i64),
+                    NodeKind::Equal => Some((l == r) as i64),
+                    NodeKind::NotEqual => Some((l != r) as i64),
+                    NodeKind::Substitution => None,
+                }?;
+                Some(Ast::truncate(ty, val))
+            },
+            _ => None,
+        }
+    }
+
+    // 浮動小数点数の定数式の評価
+    fn eval_double(&self) -> Option<f64> {
+        let val = match self {
+            Ast::FNum(val, _) => *val,
+            Ast::CastNode { hs, .. } if hs.ty().is_flonum() => hs.eval_double()?,
+            Ast::CastNode { hs, .. } if hs.ty().is_unsigned() => hs.eval()? as u64 as f64,
+            Ast::CastNode { hs, .. } => hs.eval()? as f64,
+            Ast::Node { node_kind, ty, lhs, rhs } if ty.is_flonum() => {
+                let (l, r) = (lhs.eval_double()?, rhs.eval_double()?);
+                match node_kind {
+                    NodeKind::Add => l + r,
+                    NodeKind::Sub => l - r,
+                    NodeKind::Mul => l * r,
+                    NodeKind::Div => l / r,
+                    _ => return None,
+                }
+            },
+            _ => return None,
+        };
+        // floatの精度に丸める
+        match self.ty() {
+            Type::Float => Some(val as f32 as f64),
+            _ => Some(val),
+        }
+    }
+
+    // 値を型の大きさに切り詰めて符号拡張(符号なしならゼロ拡張)する
+    fn truncate(ty: &Type, val: i64) -> i64 {
+        match ty {
+            Type::Bool => (val != 0) as i64,
+            Type::Char => val as i8 as i64,
+            Type::UChar => val as u8 as i64,
+            Type::Short => val as i16 as i64,
+            Type::UShort => val as u16 as i64,
+            Type::Int | Type::Enum => val as i32 as i64,
+            Type::UInt => val as u32 as i64,
+            _ => val,
+        }
+    }
+
+    // program      = (ident func | global)*
+    // func         = "(" params ")" ("{" stmt* "}" | ";")
+    // params       = "void" | (param ("," param)* ("," "...")?)?
+    // param        = declspec "*"* ident? type_suffix | ident
+    // global       = storage? declspec declarator (func | ("=" assign)? ("," declarator ("=" assign)?)* ";")
+    //              | typedef
+    // stmt         = expr ";"
+    //              | declaration
+    //              | "{" stmt* "}"
+    //              | "if" "(" expr ")" stmt ("else" stmt)?
+    //              | "while" "(" expr ")" stmt
+    //              | "for" "(" expr? ";" expr? ";" expr? ")" stmt
+    //              | "return" expr? ";"
+    // declaration  = storage? declspec declarator ("=" assign)? ("," declarator ("=" assign)?)* ";"
+    //              | typedef
+    // storage      = ("static" | "extern")+
+    // typedef      = "typedef" declspec declarator ("," declarator)* ";"
+    // declspec     = qualifier* (("int" | "char" | "short" | "long" | "signed" | "unsigned" | "float" | "double" | qualifier)+
+    //              | "_Bool" | "void" | struct_decl | enum_decl | typedef_name) qualifier*
+    // qualifier    = "const" | "volatile"
+    // struct_decl  = ("struct" | "union") ident? ("{" (declspec declarator ("," declarator)* ";")* "}")?
+    // enum_decl    = "enum" ident? ("{" ident ("=" assign)? ("," ident ("=" assign)?)* ","? "}")?
+    // declarator   = ("*" qualifier*)* ("(" declarator ")" ("(" params ")" | type_suffix) | ident type_suffix)
+    // type_suffix  = ("[" expr "]")*
+    // type_name    = declspec declarator (identは省略)
+    // expr         = assign
+    // assign       = equality ("=" assign)?
+    // equality     = relational ("==" relational | "!=" relational)*
+    // relationl    = shift ("<" shift | ">" shift | "<=" shift | ">=" shift)*
+    // shift        = add ("<<" add | ">>" add)*
+    // add           = mul ("+" mul | "-" mul) *
+    // mul          = unary ("*" unary | "/" unary)*
+    // unary        = "+"? unary | "-"? unary | "*" unary | "&" unary
+    //              | "sizeof" unary | "sizeof" "(" type_name ")"
+    //              | "(" type_name ")" unary | "(" expr ")" postfix_suffix | postfix
+    // postfix      = primary ("[" expr "]" | "." ident | "->" ident | "(" (assign ",")* assign? ")")*
+    // primary      = num | fnum | str+ | ident | va_builtin
+    // va_builtin   = ("va_start" | "va_copy") "(" assign "," assign ")" | "va_end" "(" assign ")"
+    //              | "va_arg" "(" assign "," type_name ")"
+    // 本当はunaryのところは符号付数字であるが、これでも構文解析はできるためこれで行く
+    pub fn program<Tokens>(tokens: &mut Peekable<Tokens>) -> Result<Vec<Ast>, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // ローカル変数はfuncごとに破棄し、グローバル変数はprogramで共有する
+        // control_val(ラベルのための連番)はprogramで一つで問題なし
+        let mut func_list = Vec::new();
+        let mut variable_list = VariableList::new();
+        let mut control_val = ControlVal::new();
+        while tokens.peek().unwrap().val != TokenKind::EOF {
+            if Ast::is_type_name(tokens.peek().unwrap(), &variable_list) {
+                let mut globals = Ast::global(tokens, &mut variable_list, &mut control_val)?;
+                func_list.append(&mut globals);
+                continue;
+            }
+            // 返り値の型を省略した関数はintを返す
+            let func_name = match tokens.next().unwrap() {
+                match_token_ident!(func_name) => func_name,
+                match_token_nothing!(pos, line_num) => return Err(AstError::undecrlared_function(pos, line_num)),
+            };
+            if let Some(func) = Ast::func(tokens, &mut variable_list, &mut control_val, func_name, Type::Int, Storage::Default)? {
+                func_list.push(func);
+            }
+        }
+        func_list.append(&mut variable_list.globals);
+        func_list.append(&mut variable_list.static_locals);
+        for (i, data) in variable_list.string_literals.into_iter().enumerate() {
+            func_list.push(Ast::StringLiteralNode { name: format!(".LC{}", i), data });
+        }
+        Ok(func_list)
+    }
+
+    fn global<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, control_val: &mut ControlVal) -> Result<Vec<Ast>, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let mut res_global: Vec<Ast> = Vec::new();
+        if let match_token_ok!(TokenKind::Typedef) = tokens.peek().unwrap() {
+            Ast::typedef(tokens, variable_list)?;
+            return Ok(res_global);
+        }
+        let storage = Ast::storage(tokens)?;
+        let base_ty = Ast::declspec(tokens, variable_list)?;
+        // struct S { ... }; のように型の宣言のみの場合
+        if let match_token_ok!(TokenKind::SemiColon) = tokens.peek().unwrap() {
+            tokens.next();
+            return Ok(res_global);
+        }
+        loop {
+            let Token { pos, line_num, .. } = *tokens.peek().unwrap();
+            let (name, ty) = Ast::declarator(tokens, variable_list, base_ty.clone())?;
+            // 関数の定義またはプロトタイプ宣言
+            if let match_token_ok!(TokenKind::LParen) = tokens.peek().unwrap() {
+                if let Some(func) = Ast::func(tokens, variable_list, control_val, name, ty, storage)? {
+                    res_global.push(func);
+                }
+                return Ok(res_global);
+            }
+            if *ty.unqual() == Type::Void {
+                return Err(AstError::void_value(pos, line_num));
+            }
+            // 不完全型の変数はexternでのみ宣言できる
+            if storage != Storage::Extern && ty.is_incomplete() {
+                return Err(AstError::incomplete_type(pos, line_num));
+            }
+            // 同じ名前は同じ型で宣言しなければならない
+            if variable_list.get_global(&name).is_some_and(|old| *old != ty) {
+                return Err(AstError::conflicting_declaration(pos, line_num));
+            }
+            variable_list.declare_global(name.clone(), ty.clone());
+            // externは先の宣言の結合を引き継ぐが, 記憶域クラスのない変数の定義は常に外部結合
+            let is_static = match storage {
+                Storage::Default => false,
+                _ => variable_list.is_internal(&name, storage),
+            };
+            let init = Ast::global_init(tokens, variable_list, &ty)?;
+            // 初期値のないexternは他で定義された変数の宣言
+            if (storage != Storage::Extern || init.is_some()) && !variable_list.define_global(name, ty, init, is_static) {
+                return Err(AstError::conflicting_declaration(pos, line_num));
+            }
+            match tokens.next().unwrap() {
+                match_token_ok!(TokenKind::Comma) => continue,
+                match_token_ok!(TokenKind::SemiColon) => break,
+                match_token_nothing!(pos, line_num) => return Err(AstError::require_semicolon(pos, line_num)),
+            }
+        }
+        Ok(res_global)
+    }
+
+    // グローバル変数とstaticなローカル変数の初期値(定数式のみ)
+    fn global_init<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, ty: &Type) -> Result<Option<i64>, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let Token { pos, line_num, .. } = match tokens.peek().unwrap() {
+            match_token_ok!(TokenKind::Substitution) => tokens.next().unwrap(),
+            _ => return Ok(None),
+        };
+        if ty.is_array() {
+            return Err(AstError::invalid_operand(pos, line_num));
+        }
+        // 変数の型に変換した値で初期化する
+        // 浮動小数点数はビット列を初期値とする
+        let init = Ast::cast_node(ty.clone(), Ast::assign(tokens, variable_list)?);
+        let val = match ty.unqual() {
+            Type::Float => init.eval_double().map(|val| (val as f32).to_bits() as i64),
+            Type::Double => init.eval_double().map(|val| val.to_bits() as i64),
+            _ => init.eval(),
+        };
+        match val {
+            Some(val) => Ok(Some(val)),
+            None => Err(AstError::not_constant(pos, line_num)),
+        }
+    }
+
+    // "static"と"extern"は同時に指定できない
+    fn storage<Tokens>(tokens: &mut Peekable<Tokens>) -> Result<Storage, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let mut storage = Storage::Default;
+        loop {
+            let next = match tokens.peek().unwrap() {
+                match_token_ok!(TokenKind::Static) => Storage::Static,
+                match_token_ok!(TokenKind::Extern) => Storage::Extern,
+                _ => return Ok(storage),
+            };
+            let Token { pos, line_num, .. } = tokens.next().unwrap();
+            if storage != Storage::Default && storage != next {
+                return Err(AstError::not_pattern_matching(pos, line_num));
+            }
+            storage = next;
+        }
+    }
+
+    // 定義ならFuncNode, プロトタイプ宣言ならNoneを返す
+    fn func<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, control_val: &mut ControlVal, func_name: String, return_ty: Type, storage: Storage) -> Result<Option<Ast>, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let return_ty = return_ty.unqual().clone();
+        let (decl_pos, decl_line_num) = match tokens.next().unwrap() {
+            match_token!(TokenKind::LParen, pos, line_num) => (pos, line_num),
+            match_token_nothing!(pos, line_num) => return Err(AstError::require_left_parenth(pos, line_num)),
+        };
+        let (params, is_prototype, is_variadic) = Ast::params(tokens, variable_list)?;
+        let param_tys = if is_prototype {
+            Some(params.iter().map(|(_, ty)| ty.clone()).collect())
+        } else {
+            None
+        };
+        // 同じ関数の宣言は返り値と, 引数の型があれば引数の型も一致しなければならない
+        // 引数の型のない宣言(f()やK&R形式)は先の宣言の型を引き継ぐ
+        let func_ty = Type::func(return_ty.clone(), param_tys, is_variadic);
+        let func_ty = match (variable_list.get_global(&func_name), &func_ty) {
+            (None, _) => func_ty,
+            (Some(Type::Func(old)), Type::Func(new)) if old.ret == new.ret => match (&old.params, &new.params) {
+                (Some(_), None) => Type::Func(old.clone()),
+                (Some(old_params), Some(new_params)) if old_params != new_params || old.is_variadic != new.is_variadic => {
+                    return Err(AstError::conflicting_declaration(decl_pos, decl_line_num));
+                },
+                _ => func_ty,
+            },
+            _ => return Err(AstError::conflicting_declaration(decl_pos, decl_line_num)),
+        };
+        // 再帰呼び出しのため本体より先に宣言する
+        variable_list.declare_global(func_name.clone(), func_ty);
+        let is_static = variable_list.is_internal(&func_name, storage);
+        // func()
+        //      ^
+        // ここまで構文解析が完了
+        let Token { pos, line_num, .. } = match tokens.next().unwrap() {
+            match_token_ok!(TokenKind::SemiColon) => return Ok(None),
+            token @ match_token_ok!(TokenKind::LCuryBra) => token,
+            match_token_nothing!(pos, line_num) => return Err(AstError::require_semicolon(pos, line_num)),
+        };
+        if return_ty.is_incomplete() {
+            return Err(AstError::incomplete_type(decl_pos, decl_line_num));
+        }
+        // 関数の本体は一度しか定義できない
+        if !variable_list.defined_funcs.insert(func_name.clone()) {
+            return Err(AstError::conflicting_declaration(decl_pos, decl_line_num));
+        }
+        variable_list.reset_local(return_ty);
+        // 引数と関数本体のスコープ
+        variable_list.enter_scope();
+        // 可変長引数の関数はレジスタの引数の保存領域(176byte)と, va_startでコピーするva_listの要素を確保する
+        // va_listの要素は保存領域の直前に置く
+        let va_area = if is_variadic {
+            variable_list.alloc_temp(&Type::array_of(Type::Char, 176));
+            let va_elem = variable_list.va_elem.clone();
+            Some(variable_list.declare("__va_area__".to_string(), va_elem))
+        } else {
+            None
+        };
+        let mut param_nodes = Vec::new();
+        for (name, ty) in params.into_iter() {
+            // 定義では引数名を省略できない
+            let name = match name {
+                Some(name) => name,
+                None => return Err(AstError::require_ident(pos, line_num)),
+            };
+            if variable_list.is_declared_in_current(&name) {
+                return Err(AstError::conflicting_declaration(pos, line_num));
+            }
+            if ty.is_incomplete() {
+                return Err(AstError::incomplete_type(pos, line_num));
+            }
+            // variable_list(ローカル変数の参照に使用, argumentもローカル変数として使用するため追加)
+            let offset = variable_list.declare(name.clone(), ty.clone());
+            // params(レジスタからローカル変数へのコピーに使用)
+            param_nodes.push(Ast::Ident(name, offset, ty));
+        }
+        let mut res_stmt: Vec<Ast> = Vec::new();
+        loop {
+            if let match_token_ok!(TokenKind::RCuryBra) = tokens.peek().unwrap() {
+                tokens.next();
+                break;
+            }
+            let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+            res_stmt.push(stmt);
+        }
+        variable_list.leave_scope();
+        Ok(Some(Ast::func_node(param_nodes, variable_list.stack_size(), func_name, Ast::block_node(res_stmt), variable_list.ret_buf, va_area, is_static)))
+    }
+
+    // 引数の名前(プロトタイプでは省略可)と型のリスト, 引数の型を検査できるか, 可変長引数か
+    // f()と型のない引数(K&R形式)は呼び出し時に検査しない
+    #[allow(clippy::type_complexity)]
+    fn params<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<(Vec<(Option<String>, Type)>, bool, bool), AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let mut params = Vec::new();
+        if let match_token_ok!(TokenKind::RParen) = tokens.peek().unwrap() {
+            tokens.next();
+            return Ok((params, false, false));
+        }
+        let mut is_prototype = true;
+        loop {
+            // "..."は最後の引数
+            if let match_token_ok!(TokenKind::Ellipsis) = tokens.peek().unwrap() {
+                tokens.next();
+                return match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::RParen) => Ok((params, is_prototype, true)),
+                    match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
+                };
+            }
+            if Ast::is_type_name(tokens.peek().unwrap(), variable_list) {
+                let base_ty = Ast::declspec(tokens, variable_list)?;
+                // f(void)は引数なし
+                if let (true, Type::Void, match_token_ok!(TokenKind::RParen)) = (params.is_empty(), &base_ty, tokens.peek().unwrap()) {
+                    tokens.next();
+                    return Ok((params, true, false));
+                }
+                // 配列の引数はポインタとして扱う
+                let (name, ty) = Ast::abstract_declarator(tokens, variable_list, base_ty)?;
+                params.push((name, ty.decay()));
+            } else {
+                match tokens.next().unwrap() {
+                    match_token_ident!(name) => params.push((Some(name), Type::Int)),
+                    match_token_nothing!(pos, line_num) => return Err(AstError::not_pattern_matching(pos, line_num)),
+                }
+                is_prototype = false;
+            }
+            match tokens.next().unwrap() {
+                match_token_ok!(TokenKind::Comma) => continue,
+                match_token_ok!(TokenKind::RParen) => break,
+                match_token_nothing!(pos, line_num) => return Err(AstError::unclosed_parenth(pos, line_num)),
+            }
+        }
+        Ok((params, is_prototype, false))
+    }
+
+    fn stmt<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, control_val: &mut ControlVal) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        match tokens.peek().unwrap() {
+            match_token_ok!(TokenKind::Return) => {
+                match tokens.next().unwrap() {
+                    match_token!(TokenKind::Return, pos, line_num) => {
+                        // 値を返さないreturn
+                        if let match_token_ok!(TokenKind::SemiColon) = tokens.peek().unwrap() {
+                            tokens.next();
+                            return Ok(Ast::return_node(Ast::num(0), None));
+                        }
+                        // 返り値は関数の返り値の型に変換する
+                        let expr = Ast::value(Ast::expr(tokens, variable_list)?, pos, line_num)?;
+                        if variable_list.return_ty.discards_qualifiers(&expr.ty()) {
+                            return Err(AstError::discard_qualifiers(pos, line_num));
+                        }
+                        // 返り値の型に代入できる値のみ返せる(voidの関数は値を返せない)
+                        if !Ast::is_assignable(&variable_list.return_ty, &expr) {
+                            return Err(AstError::return_mismatch(pos, line_num));
+                        }
+                        let expr = match &variable_list.return_ty {
+                            return_ty if return_ty.is_numeric() && expr.ty().is_numeric() => Ast::cast_node(return_ty.clone(), expr),
+                            _ => expr,
+                        };
+                        match tokens.next().unwrap() {
+                            match_token_ok!(TokenKind::SemiColon) => Ok(Ast::return_node(expr, variable_list.ret_buf)),
+                            match_token_nothing!(pos, line_num) => Err(AstError::require_semicolon(pos, line_num)),
+                        }
+                    },
+                    _ => unreachable!(),
+                }
+            },
+            match_token_ok!(TokenKind::If) => {
+                match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::If) => {
+                        match tokens.next().unwrap() {
+                            match_token_ok!(TokenKind::LParen) => {
+                                let expr = Ast::expr(tokens, variable_list)?;
+                                match tokens.next().unwrap() {
+                                    match_token_ok!(TokenKind::RParen) => {
+                                        let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                        match tokens.peek().unwrap() {
+                                            match_token_ok!(TokenKind::Else) => {
+                                                match tokens.next().unwrap() {
+                                                    match_token_ok!(TokenKind::Else) => {
+                                                        let stmt_second = Ast::stmt(tokens, variable_list, control_val)?;
+                                                        Ok(Ast::if_else_node(control_val.val_if_else(), expr, stmt, stmt_second))
+                                                    },
+                                                    _ => unreachable!(),
+                                                }
+                                            }
+                                            _ => Ok(Ast::if_node(control_val.val_if_else(), expr, stmt))
+                                        }
+                                    },
+                                    match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
+                                }
+                            },
+                            match_token_nothing!(pos, line_num) => Err(AstError::require_left_parenth(pos, line_num)),
+                        }
+                    },
+                    _ => unreachable!(),
+                }
+            },
+            match_token_ok!(TokenKind::While) => {
+                match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::While) => {
+                        match tokens.next().unwrap() {
+                            match_token_ok!(TokenKind::LParen) => {
+                                let expr = Ast::expr(tokens, variable_list)?;
+                                match tokens.next().unwrap() {
+                                    match_token_ok!(TokenKind::RParen) => {
+                                        let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                        Ok(Ast::while_node(control_val.val_while(), expr, stmt))
+                                    },
+                                    match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
+                                }
+                            },
+                            match_token_nothing!(pos, line_num) => Err(AstError::require_left_parenth(pos, line_num)),
+                        }
+                    },
+                    _ => unreachable!(),
+                }
+            },
+            match_token_ok!(TokenKind::For) => {
+                let for_num = control_val.val_for();
+                match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::For) => {
+                        match tokens.next().unwrap() {
+                            match_token_ok!(TokenKind::LParen) => {
+                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                //        ^
+                                match tokens.peek().unwrap() {
+                                    match_token_ok!(TokenKind::SemiColon) => {
+                                        match tokens.next().unwrap() {
+                                            match_token_ok!(TokenKind::SemiColon) => {
+                                            // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                            //                  ^
+                                                match tokens.peek().unwrap() {
+                                                    match_token_ok!(TokenKind::SemiColon) => {
+                                                        match tokens.next().unwrap() {
+                                                            match_token_ok!(TokenKind::SemiColon) => {
+                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                //                            ^
+                                                                match tokens.peek().unwrap() {
+                                                                    match_token_ok!(TokenKind::RParen) => {
+                                                                        match tokens.next().unwrap() {
+                                                                            match_token_ok!(TokenKind::RParen) => {
+                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                                //                                      ^
+                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                                                                Ok(Ast::for_node(for_num, None, None, None, stmt))
+                                                                            },
+                                                                            _ => unreachable!(),
+                                                                        }
+                                                                    },
+                                                                    _ => {
+                                                                        let expr_third = Ast::expr(tokens, variable_list)?;
+                                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                        //                                   ^
+                                                                        match tokens.next().unwrap() {
+                                                                            match_token_ok!(TokenKind::RParen) => {
+                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                                //                                      ^
+                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                                                                Ok(Ast::for_node(for_num, None, None, Some(expr_third), stmt))
+                                                                            },
+                                                                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num))
+                                                                        }
+                                                                    }
+                                                                }
+                                                            }
+                                                            _ => unreachable!(),
+                                                        }
+                                                    }
+                                                    _ => {
+                                                        let expr_second = Ast::expr(tokens, variable_list)?;
+                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                        //                         ^
+                                                        match tokens.next().unwrap() {
+                                                            match_token_ok!(TokenKind::SemiColon) => {
+                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                //                            ^
+                                                                match tokens.peek().unwrap() {
+                                                                    match_token_ok!(TokenKind::RParen) => {
+                                                                        match tokens.next().unwrap() {
+                                                                            match_token_ok!(TokenKind::RParen) => {
+                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                                //                                      ^
+                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                                                                Ok(Ast::for_node(for_num, None, Some(expr_second), None, stmt))
+                                                                            },
+                                                                            _ => unreachable!(),
+                                                                        }
+                                                                    },
+                                                                    _ => {
+                                                                        let expr_third = Ast::expr(tokens, variable_list)?;
+                                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                        //                                   ^
+                                                                        match tokens.next().unwrap() {
+                                                                            match_token_ok!(TokenKind::RParen) => {
+                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                                //                                      ^
+                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                                                                Ok(Ast::for_node(for_num, None, Some(expr_second), Some(expr_third), stmt))
+                                                                            },
+                                                                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num))
+                                                                        }
+                                                                    }
+                                                                }
+                                                            }
+                                                            _ => unreachable!(),
+                                                        }
+                                                    }
+                                                }
+                                            },
+                                            _ => unreachable!(),
+                                        }
+                                    },
+                                    _ => {
+                                        let expr_first = Ast::expr(tokens, variable_list)?;
+                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                        //               ^
+                                        match tokens.next().unwrap() {
+                                            match_token_ok!(TokenKind::SemiColon) => {
+                                            // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                            //                  ^
+                                                match tokens.peek().unwrap() {
+                                                    match_token_ok!(TokenKind::SemiColon) => {
+                                                        match tokens.next().unwrap() {
+                                                            match_token_ok!(TokenKind::SemiColon) => {
+                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                //                            ^
+                                                                match tokens.peek().unwrap() {
+                                                                    match_token_ok!(TokenKind::RParen) => {
+                                                                        match tokens.next().unwrap() {
+                                                                            match_token_ok!(TokenKind::RParen) => {
+                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                                //                                      ^
+                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                                                                Ok(Ast::for_node(for_num, Some(expr_first), None, None, stmt))
+                                                                            },
+                                                                            _ => unreachable!(),
+                                                                        }
+                                                                    },
+                                                                    _ => {
+                                                                        let expr_third = Ast::expr(tokens, variable_list)?;
+                                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                        //                                   ^
+                                                                        match tokens.next().unwrap() {
+                                                                            match_token_ok!(TokenKind::RParen) => {
+                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                                //                                      ^
+                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                                                                Ok(Ast::for_node(for_num, Some(expr_first), None, Some(expr_third), stmt))
+                                                                            },
+                                                                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num))
+                                                                        }
+                                                                    }
+                                                                }
+                                                            }
+                                                            _ => unreachable!(),
+                                                        }
+                                                    }
+                                                    _ => {
+                                                        let expr_second = Ast::expr(tokens, variable_list)?;
+                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                        //                         ^
+                                                        match tokens.next().unwrap() {
+                                                            match_token_ok!(TokenKind::SemiColon) => {
+                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                //                            ^
+                                                                match tokens.peek().unwrap() {
+                                                                    match_token_ok!(TokenKind::RParen) => {
+                                                                        match tokens.next().unwrap() {
+                                                                            match_token_ok!(TokenKind::RParen) => {
+                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                                //                                      ^
+                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                                                                Ok(Ast::for_node(for_num, Some(expr_first), Some(expr_second), None, stmt))
+                                                                            },
+                                                                            _ => unreachable!(),
+                                                                        }
+                                                                    },
+                                                                    _ => {
+                                                                        let expr_third = Ast::expr(tokens, variable_list)?;
+                                                                        // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                        //                                   ^
+                                                                        match tokens.next().unwrap() {
+                                                                            match_token_ok!(TokenKind::RParen) => {
+                                                                                // "for" "(" expr? ";" expr? ";" expr? ")" stmt
+                                                                                //                                      ^
+                                                                                let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                                                                                Ok(Ast::for_node(for_num, Some(expr_first), Some(expr_second), Some(expr_third), stmt))
+                                                                            },
+                                                                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num))
+                                                                        }
+                                                                    }
+                                                                }
+                                                            }
+                                                            _ => unreachable!(),
+                                                        }
+                                                    }
+                                                }
+                                            },
+                                            match_token_nothing!(pos, line_num) => Err(AstError::require_semicolon(pos, line_num)),
+                                        }
+                                    }
+                                }
+                            },
+                            match_token_nothing!(pos, line_num) => Err(AstError::require_left_parenth(pos, line_num)),
+                        }
+                    },
+                    _ => unreachable!(),
+                }
+            },
+            token if Ast::is_type_name(token, variable_list) => Ast::declaration(tokens, variable_list),
+            match_token_ok!(TokenKind::LCuryBra) => {
+                match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::LCuryBra) => {
+                        // ブロック内の宣言はブロックの終わりまで有効
+                        variable_list.enter_scope();
+                        let mut res_stmt: Vec<Ast> = Vec::new();
+                        loop {
+                            if let match_token_ok!(TokenKind::RCuryBra) = tokens.peek().unwrap() {
+                                tokens.next();
+                                break;
+                            }
+                            let stmt = Ast::stmt(tokens, variable_list, control_val)?;
+                            res_stmt.push(stmt);
+                            // エラー処理 todo
+                            // if tokens.peek().unwrap().val == TokenKind::RCuryBra {
+                            //     match tokens.next().unwrap() {
+                            //         match_token!(TokenKind::RCuryBra, _pos) => break,
+                            //         _ => unreachable!(),
+                            //     }
+                            // }
+                        }
+                        variable_list.leave_scope();
+                        Ok(Ast::block_node(res_stmt))
+                    }
+                    _ => unreachable!(),
+                }
+            },
+            _ => {
+                let expr = Ast::expr(tokens, variable_list)?;
+                match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::SemiColon) => Ok(expr),
+                    match_token_nothing!(pos, line_num) => Err(AstError::require_semicolon(pos, line_num)),
+                }
+            }
+        }
+    }
+
+    fn declaration<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // 初期化式はSubstitutionとして扱う
+        let mut res_init: Vec<Ast> = Vec::new();
+        if let match_token_ok!(TokenKind::Typedef) = tokens.peek().unwrap() {
+            Ast::typedef(tokens, variable_list)?;
+            return Ok(Ast::block_node(res_init));
+        }
+        let storage = Ast::storage(tokens)?;
+        let base_ty = Ast::declspec(tokens, variable_list)?;
+        if let match_token_ok!(TokenKind::SemiColon) = tokens.peek().unwrap() {
+            tokens.next();
+            return Ok(Ast::block_node(res_init));
+        }
+        loop {
+            let Token { pos, line_num, .. } = *tokens.peek().unwrap();
+            let (ident, ty) = Ast::declarator(tokens, variable_list, base_ty.clone())?;
+            if *ty.unqual() == Type::Void {
+                return Err(AstError::void_value(pos, line_num));
+            }
+            // 同じブロックで同じ名前を二度宣言できない
+            if storage != Storage::Extern && variable_list.is_declared_in_current(&ident) {
+                return Err(AstError::conflicting_declaration(pos, line_num));
+            }
+            if storage != Storage::Extern && ty.is_incomplete() {
+                return Err(AstError::incomplete_type(pos, line_num));
+            }
+            match storage {
+                // staticは呼び出しをまたいで値を保持するため, 初期化は一度だけ
+                Storage::Static => {
+                    let init = Ast::global_init(tokens, variable_list, &ty)?;
+                    variable_list.declare_static_local(ident, ty, init);
+                },
+                // 他で定義されたグローバル変数をこのスコープで参照する
+                Storage::Extern => variable_list.declare_global(ident, ty),
+                Storage::Default => {
+                    let offset = variable_list.declare(ident.clone(), ty.clone());
+                    if let match_token!(TokenKind::Substitution, pos, line_num) = *tokens.peek().unwrap() {
+                        tokens.next();
+                        let r_ast = Ast::assign(tokens, variable_list)?;
+                        res_init.push(Ast::binary_node(NodeKind::Substitution, Ast::Ident(ident, offset, ty), r_ast, pos, line_num)?);
+                    }
+                },
+            }
+            match tokens.next().unwrap() {
+                match_token_ok!(TokenKind::Comma) => continue,
+                match_token_ok!(TokenKind::SemiColon) => break,
+                match_token_nothing!(pos, line_num) => return Err(AstError::require_semicolon(pos, line_num)),
+            }
+        }
+        Ok(Ast::block_node(res_init))
+    }
+
+    // typedef名は変数と同じ名前空間にあるため, 識別子はスコープを見て判断する
+    fn typedef<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<(), AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // "typedef" declspec declarator ("," declarator)* ";"
+        //     ^
+        tokens.next();
+        let base_ty = Ast::declspec(tokens, variable_list)?;
+        loop {
+            let (name, ty) = Ast::declarator(tokens, variable_list, base_ty.clone())?;
+            variable_list.declare_typedef(name, ty);
+            match tokens.next().unwrap() {
+                match_token_ok!(TokenKind::Comma) => continue,
+                match_token_ok!(TokenKind::SemiColon) => break,
+                match_token_nothing!(pos, line_num) => return Err(AstError::require_semicolon(pos, line_num)),
+            }
+        }
+        Ok(())
+    }
+
+    // 型名(または宣言)の開始となるトークンか
+    fn is_type_name(token: &Token, variable_list: &VariableList) -> bool {
+        match &token.val {
+            TokenKind::Int | TokenKind::Char | TokenKind::Short | TokenKind::Long | TokenKind::Signed | TokenKind::Unsigned | TokenKind::Bool | TokenKind::Void
+            | TokenKind::FloatType | TokenKind::DoubleType | TokenKind::Struct | TokenKind::Union | TokenKind::Enum | TokenKind::Typedef
+            | TokenKind::Static | TokenKind::Extern | TokenKind::Const | TokenKind::Volatile => true,
+            TokenKind::Ident(name) => variable_list.get_typedef(name).is_some(),
+            _ => false,
+        }
+    }
+
+    fn declspec<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Type, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // const int, int constのように修飾は型指定子の前後どちらにも書ける
+        let mut qualifiers = Ast::type_qualifiers(tokens, Qualifiers::default());
+        let ty = Ast::type_specifier(tokens, variable_list, &mut qualifiers)?;
+        let qualifiers = Ast::type_qualifiers(tokens, qualifiers);
+        Ok(ty.qualify(qualifiers))
+    }
+
+    fn type_qualifiers<Tokens>(tokens: &mut Peekable<Tokens>, mut qualifiers: Qualifiers) -> Qualifiers
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        loop {
+            match tokens.peek().unwrap().val {
+                TokenKind::Const => qualifiers.is_const = true,
+                TokenKind::Volatile => qualifiers.is_volatile = true,
+                _ => return qualifiers,
+            }
+            tokens.next();
+        }
+    }
+
+    fn type_specifier<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, qualifiers: &mut Qualifiers) -> Result<Type, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // _Bool, void, struct, union, enum, typedef名は他の型指定子と組み合わせない
+        match tokens.peek().unwrap() {
+            match_token_ok!(TokenKind::Struct) => {
+                tokens.next();
+                return Ast::struct_decl(tokens, variable_list, false);
+            },
+            match_token_ok!(TokenKind::Union) => {
+                tokens.next();
+                return Ast::struct_decl(tokens, variable_list, true);
+            },
+            match_token_ok!(TokenKind::Enum) => {
+                tokens.next();
+                return Ast::enum_decl(tokens, variable_list);
+            },
+            match_token_ident!(name, pos, line_num) => {
+                return match variable_list.get_typedef(name) {
+                    Some(ty) => {
+                        let ty = ty.clone();
+                        tokens.next();
+                        Ok(ty)
+                    },
+                    None => Err(AstError::not_pattern_matching(*pos, *line_num)),
+                };
+            },
+            match_token_ok!(TokenKind::Bool) => {
+                tokens.next();
+                return Ok(Type::Bool);
+            },
+            match_token_ok!(TokenKind::Void) => {
+                tokens.next();
+                return Ok(Type::Void);
+            },
+            _ => (),
+        }
+        // 整数型は型指定子の個数の組み合わせで決まる(順不同)
+        // long long intやshort signedなども同じ型として扱う
+        let Token { pos, line_num, .. } = *tokens.peek().unwrap();
+        let (mut char_num, mut short_num, mut int_num, mut long_num) = (0, 0, 0, 0);
+        let (mut signed_num, mut unsigned_num) = (0, 0);
+        let (mut float_num, mut double_num) = (0, 0);
+        loop {
+            match tokens.peek().unwrap().val {
+                TokenKind::Char => char_num += 1,
+                TokenKind::Short => short_num += 1,
+                TokenKind::Int => int_num += 1,
+                TokenKind::Long => long_num += 1,
+                TokenKind::Signed => signed_num += 1,
+                TokenKind::Unsigned => unsigned_num += 1,
+                TokenKind::FloatType => float_num += 1,
+                TokenKind::DoubleType => double_num += 1,
+                // unsigned const intのように間に修飾があってもよい
+                TokenKind::Const => qualifiers.is_const = true,
+                TokenKind::Volatile => qualifiers.is_volatile = true,
+                _ => break,
+            }
+            tokens.next();
+        }
+        // long doubleはdoubleとして扱う
+        let other_num = char_num + short_num + int_num + signed_num + unsigned_num;
+        match (float_num, double_num, other_num, long_num) {
+            (0, 0, _, _) => (),
+            (1, 0, 0, 0) => return Ok(Type::Float),
+            (0, 1, 0, 0..=1) => return Ok(Type::Double),
+            _ => return Err(AstError::not_pattern_matching(pos, line_num)),
+        }
+        let is_unsigned = match (signed_num, unsigned_num) {
+            (0, 0) | (1, 0) => false,
+            (0, 1) => true,
+            _ => return Err(AstError::not_pattern_matching(pos, line_num)),
+        };
+        let ty = match (char_num, short_num, int_num, long_num) {
+            (1, 0, 0, 0) => Type::Char,
+            (0, 1, 0..=1, 0) => Type::Short,
+            (0, 0, 1, 0) => Type::Int,
+            // signed, unsignedのみはint
+            (0, 0, 0, 0) if signed_num + unsigned_num == 1 => Type::Int,
+            (0, 0, 0..=1, 1..=2) => Type::Long,
+            _ => return Err(AstError::not_pattern_matching(pos, line_num)),
+        };
+        if !is_unsigned {
+            return Ok(ty);
+        }
+        match ty {
+            Type::Char => Ok(Type::UChar),
+            Type::Short => Ok(Type::UShort),
+            Type::Int => Ok(Type::UInt),
+            _ => Ok(Type::ULong),
+        }
+    }
+
+    fn enum_decl<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Type, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // "enum" ident? ("{" ... "}")?
+        //         ^
+        let tag = match tokens.peek().unwrap() {
+            match_token_ident!(_tag) => match tokens.next().unwrap() {
+                match_token_ident!(tag, pos, line_num) => Some((tag, pos, line_num)),
+                _ => unreachable!(),
+            },
+            _ => None,
+        };
+        match (&tag, tokens.peek().unwrap()) {
+            (_, match_token_ok!(TokenKind::LCuryBra)) => {
+                tokens.next();
+            },
+            // enum E はタグから型を探す(enumの前方宣言はできない)
+            (Some((tag, pos, line_num)), _) => {
+                return match variable_list.get_tag(tag) {
+                    Some(Type::Enum) => Ok(Type::Enum),
+                    _ => Err(AstError::undeclared_tag(*pos, *line_num)),
+                };
+            },
+            (None, match_token_nothing!(pos, line_num)) => return Err(AstError::require_ident(*pos, *line_num)),
+        }
+        // 値が省略された列挙子は直前の値 + 1
+        let mut val: i64 = 0;
+        loop {
+            match tokens.next().unwrap() {
+                match_token_ok!(TokenKind::RCuryBra) => break,
+                match_token_ident!(name) => {
+                    if let match_token_ok!(TokenKind::Substitution) = tokens.peek().unwrap() {
+                        let Token { pos, line_num, .. } = tokens.next().unwrap();
+                        val = match Ast::assign(tokens, variable_list)?.eval() {
+                            Some(val) => val,
+                            None => return Err(AstError::not_constant(pos, line_num)),
+                        };
+                    }
+                    variable_list.declare_enum_constant(name, val);
+                    val += 1;
+                },
+                match_token_nothing!(pos, line_num) => return Err(AstError::require_ident(pos, line_num)),
+            }
+            match tokens.next().unwrap() {
+                match_token_ok!(TokenKind::Comma) => continue,
+                match_token_ok!(TokenKind::RCuryBra) => break,
+                match_token_nothing!(pos, line_num) => return Err(AstError::require_commma(pos, line_num)),
+            }
+        }
+        if let Some((tag, _, _)) = tag {
+            variable_list.declare_tag(tag, Type::Enum);
+        }
+        Ok(Type::Enum)
+    }
+
+    fn struct_decl<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, is_union: bool) -> Result<Type, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // ("struct" | "union") ident? ("{" ... "}")?
+        //                       ^
+        let tag = match tokens.peek().unwrap() {
+            match_token_ident!(_tag) => match tokens.next().unwrap() {
+                match_token_ident!(tag) => Some(tag),
+                _ => unreachable!(),
+            },
+            _ => None,
+        };
+        let struct_ref = match (&tag, tokens.peek().unwrap()) {
+            (_, match_token_ok!(TokenKind::LCuryBra)) => {
+                tokens.next();
+                // 同じスコープで宣言済みのタグ(struct S;)であれば, その定義を埋める
+                match tag.as_ref().and_then(|tag| variable_list.get_current_tag(tag)) {
+                    Some(Type::Struct(struct_ref)) => struct_ref.clone(),
+                    _ => StructRef::new(tag.clone(), is_union),
+                }
+            },
+            // struct S はタグから型を探す. 未宣言なら不完全型として宣言する
+            (Some(tag), _) => {
+                if let Some(ty) = variable_list.get_tag(tag) {
+                    return Ok(ty.clone());
+                }
+                let ty = Type::Struct(StructRef::new(Some(tag.clone()), is_union));
+                variable_list.declare_tag(tag.clone(), ty.clone());
+                return Ok(ty);
+            },
+            (None, match_token_nothing!(pos, line_num)) => return Err(AstError::require_ident(*pos, *line_num)),
+        };
+        // メンバ内で自身を参照できるよう先にタグを登録する
+        if let Some(tag) = tag {
+            variable_list.declare_tag(tag, Type::Struct(struct_ref.clone()));
+        }
+        let mut members = Vec::new();
+        loop {
+            if let match_token_ok!(TokenKind::RCuryBra) = tokens.peek().unwrap() {
+                tokens.next();
+                break;
+            }
+            let base_ty = Ast::declspec(tokens, variable_list)?;
+            loop {
+                // 自身を含む不完全型のメンバは持てない
+                let Token { pos, line_num, .. } = *tokens.peek().unwrap();
+                let member = Ast::declarator(tokens, variable_list, base_ty.clone())?;
+                if member.1.is_incomplete() {
+                    return Err(AstError::incomplete_type(pos, line_num));
+                }
+                members.push(member);
+                match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::Comma) => continue,
+                    match_token_ok!(TokenKind::SemiColon) => break,
+                    match_token_nothing!(pos, line_num) => return Err(AstError::require_semicolon(pos, line_num)),
+                }
+            }
+        }
+        struct_ref.define(members);
+        Ok(Type::Struct(struct_ref))
+    }
+
+    fn pointers<Tokens>(tokens: &mut Peekable<Tokens>, mut ty: Type) -> Type
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // int *const pの修飾はポインタ自身に付く
+        while let match_token_ok!(TokenKind::Asterisk) = tokens.peek().unwrap() {
+            tokens.next();
+            ty = Type::pointer_to(ty).qualify(Ast::type_qualifiers(tokens, Qualifiers::default()));
+        }
+        ty
+    }
+
+    fn declarator<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, ty: Type) -> Result<(String, Type), AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let Token { pos, line_num, .. } = *tokens.peek().unwrap();
+        match Ast::abstract_declarator(tokens, variable_list, ty)? {
+            (Some(ident), ty) => Ok((ident, ty)),
+            (None, _) => Err(AstError::require_ident(pos, line_num)),
+        }
+    }
+
+    // 名前を省略できる宣言子(引数, 型名で使う)
+    // "(" declarator ")"の型は外側の型が決まってから決まるため, 仮の型で解析して後で置き換える
+    fn abstract_declarator<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, ty: Type) -> Result<(Option<String>, Type), AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // ("*" qualifier*)* ("(" declarator ")" | ident?) type_suffix
+        //  ^
+        let ty = Ast::pointers(tokens, ty);
+        if let match_token_ok!(TokenKind::LParen) = tokens.peek().unwrap() {
+            tokens.next();
+            let placeholder = Type::Struct(StructRef::new(None, false));
+            let (ident, inner) = Ast::abstract_declarator(tokens, variable_list, placeholder.clone())?;
+            match tokens.next().unwrap() {
+                match_token_ok!(TokenKind::RParen) => (),
+                match_token_nothing!(pos, line_num) => return Err(AstError::unclosed_parenth(pos, line_num)),
+            }
+            // int (*fp)(int)の(int)
+            let ty = match tokens.peek().unwrap() {
+                match_token_ok!(TokenKind::LParen) => {
+                    tokens.next();
+                    let (params, is_prototype, is_variadic) = Ast::params(tokens, variable_list)?;
+                    let params = match is_prototype {
+                        true => Some(params.into_iter().map(|(_, ty)| ty).collect()),
+                        false => None,
+                    };
+                    Type::func(ty, params, is_variadic)
+                },
+                _ => Ast::type_suffix(tokens, variable_list, ty)?,
+            };
+            return Ok((ident, inner.replace(&placeholder, &ty)));
+        }
+        let ident = match tokens.peek().unwrap().val {
+            TokenKind::Ident(_) => match tokens.next().unwrap() {
+                match_token_ident!(ident) => Some(ident),
+                _ => unreachable!(),
+            },
+            _ => None,
+        };
+        // "*"* ident type_suffix
+        //            ^
+        let ty = Ast::type_suffix(tokens, variable_list, ty)?;
+        Ok((ident, ty))
+    }
+
+    // int a[2][3]はint[3]の配列が2個(要素が連続するのは右側の添字)
+    fn type_suffix<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, ty: Type) -> Result<Type, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        match tokens.peek().unwrap() {
+            match_token_ok!(TokenKind::LSquareBra) => {
+                let Token { pos, line_num, .. } = tokens.next().unwrap();
+                // 配列の長さは定数式
+                let len = match Ast::expr(tokens, variable_list)?.eval() {
+                    Some(len) if len >= 0 => len as usize,
+                    _ => return Err(AstError::not_constant(pos, line_num)),
+                };
+                match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::RSquareBra) => {
+                        let ty = Ast::type_suffix(tokens, variable_list, ty)?;
+                        Ok(Type::array_of(ty, len))
+                    },
+                    match_token_nothing!(pos, line_num) => Err(AstError::unclosed_square_bracket(pos, line_num)),
+                }
+            },
+            _ => Ok(ty),
+        }
+    }
+
+    fn type_name<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Type, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let ty = Ast::declspec(tokens, variable_list)?;
+        let (_, ty) = Ast::abstract_declarator(tokens, variable_list, ty)?;
+        Ok(ty)
+    }
+
+    fn expr<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        Ast::assign(tokens, variable_list)
+    }
+
+    fn assign<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let l_ast = Ast::equality(tokens, variable_list)?;
+        match tokens.peek().unwrap().val {
+            TokenKind::Substitution => {
+                match tokens.next().unwrap() {
+                    match_token!(TokenKind::Substitution, pos, line_num) => {
+                        // 配列全体へは代入できない
+                        if l_ast.ty().is_array() {
+                            return Err(AstError::invalid_operand(pos, line_num));
+                        }
+                        // constなオブジェクト(constを指すポインタの参照先を含む)へは代入できない
+                        if l_ast.object_ty().is_some_and(|ty| ty.is_const()) {
+                            return Err(AstError::assign_to_const(pos, line_num));
+                        }
+                        let r_ast = Ast::assign(tokens, variable_list)?;
+                        Ast::binary_node(NodeKind::Substitution, l_ast, r_ast, pos, line_num)
+                    },
+                    _ => unreachable!(),
+                }
+            }
+            _ => Ok(l_ast)
+        }
+    }
+
+    fn equality<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let mut l_ast = Ast::relational(tokens, variable_list)?;
+        loop {
+            match tokens.peek().unwrap() {
+                match_token_ok!(TokenKind::Equal) | match_token_ok!(TokenKind::NotEqual) => {
+                    match tokens.next().unwrap() {
+                        match_token!(TokenKind::Equal, pos, line_num) => {
+                            let r_ast = Ast::relational(tokens, variable_list)?;
+                            l_ast = Ast::binary_node(NodeKind::Equal, l_ast, r_ast, pos, line_num)?;
+                        },
+                        match_token!(TokenKind::NotEqual, pos, line_num) => {
+                            let r_ast = Ast::relational(tokens, variable_list)?;
+                            l_ast = Ast::binary_node(NodeKind::NotEqual, l_ast, r_ast, pos, line_num)?;
+                        },
+                        _ => unreachable!(),
+                    }
+                },
+                _ => return Ok(l_ast)
+            }
+        }
+    }
+
+    fn relational<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let mut l_ast = Ast::shift(tokens, variable_list)?;
+        loop {
+            match tokens.peek().unwrap() {
+                match_token_ok!(TokenKind::Small) | match_token_ok!(TokenKind::Large)
+                | match_token_ok!(TokenKind::EqualSmall) | match_token_ok!(TokenKind::EqualLarge) => {
+                    match tokens.next().unwrap() {
+                        match_token!(TokenKind::Small, pos, line_num) => {
+                            let r_ast = Ast::shift(tokens, variable_list)?;
+                            l_ast = Ast::binary_node(NodeKind::Small, l_ast, r_ast, pos, line_num)?;
+                        },
+                        match_token!(TokenKind::Large, pos, line_num) => {
+                            let r_ast = Ast::shift(tokens, variable_list)?;
+                            // l_ast = Ast::node(NodeKind::Large, l_ast, r_ast);
+                            l_ast = Ast::binary_node(NodeKind::Small, r_ast, l_ast, pos, line_num)?;
+                        },
+                        match_token!(TokenKind::EqualSmall, pos, line_num) => {
+                            let r_ast = Ast::shift(tokens, variable_list)?;
+                            l_ast = Ast::binary_node(NodeKind::EqualSmall, l_ast, r_ast, pos, line_num)?;
+                        },
+                        match_token!(TokenKind::EqualLarge, pos, line_num) => {
+                            let r_ast = Ast::shift(tokens, variable_list)?;
+                            // l_ast = Ast::node(NodeKind::EqualLarge, l_ast, r_ast);
+                            l_ast = Ast::binary_node(NodeKind::EqualSmall, r_ast, l_ast, pos, line_num)?;
+                        },
+                        _ => unreachable!(),
+                    }
+                },
+                _ => return Ok(l_ast)
+            }
+        }
+    }
+
+    fn shift<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let mut l_ast = Ast::add(tokens, variable_list)?;
+        loop {
+            match tokens.peek().unwrap() {
+                match_token_ok!(TokenKind::ShiftLeft) => {
+                    let Token { pos, line_num, .. } = tokens.next().unwrap();
+                    let r_ast = Ast::add(tokens, variable_list)?;
+                    l_ast = Ast::binary_node(NodeKind::ShiftLeft, l_ast, r_ast, pos, line_num)?;
+                },
+                match_token_ok!(TokenKind::ShiftRight) => {
+                    let Token { pos, line_num, .. } = tokens.next().unwrap();
+                    let r_ast = Ast::add(tokens, variable_list)?;
+                    l_ast = Ast::binary_node(NodeKind::ShiftRight, l_ast, r_ast, pos, line_num)?;
+                },
+                _ => return Ok(l_ast)
+            }
+        }
+    }
+
+    fn add<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        //   mul ("+" mul | "-" mul) *
+        // ^
+        let mut l_ast = Ast::mul(tokens, variable_list)?;
+        loop {
+            //   mul ("+" mul | "-" mul) *
+            //     ^
+            match tokens.peek().unwrap() {
+                match_token_ok!(TokenKind::Plus) | match_token_ok!(TokenKind::Minus) => {
+                    //   mul ("+" mul | "-" mul) *
+                    //         ^
+                    match tokens.next().unwrap() {
+                        match_token!(TokenKind::Plus, pos, line_num) => {
+                            //   mul ("+" mul | "-" mul) *
+                            //         ^
+                            let r_ast = Ast::mul(tokens, variable_list)?;
+                            //   mul ("+" mul | "-" mul) *
+                            //              ^
+                            l_ast = Ast::add_node(l_ast, r_ast, pos, line_num)?;
+                        },
+                        match_token!(TokenKind::Minus, pos, line_num) => {
+                            //   mul ("+" mul | "-" mul) *
+                            //                   ^
+                            let r_ast = Ast::mul(tokens, variable_list)?;
+                            //   mul ("+" mul | "-" mul) *
+                            //                        ^
+                            l_ast = Ast::sub_node(l_ast, r_ast, pos, line_num)?;
+                        },
+                        _ => unreachable!(),
+                    }
+                },
+                _ => return Ok(l_ast)
+            }
+        }
+    }
+
+    fn mul<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        //   unary ("*" unary | "/" unary)*
+        //  ^
+        let mut l_ast = Ast::unary(tokens, variable_list)?;
+        loop {
+            // unary ("*" unary | "/" unary)*
+            //     ^
+            match tokens.peek().unwrap() {
+                match_token_ok!(TokenKind::Asterisk) | match_token_ok!(TokenKind::Slash) => {
+                    match tokens.next().unwrap() {
+                        match_token!(TokenKind::Asterisk, pos, line_num) => {
+                            // unary ("*" unary | "/" unary)
+                            //         ^
+                            let r_ast = Ast::unary(tokens, variable_list)?;
+                            // unary ("*" unary | "/" unary)
+                            //                ^
+                            l_ast = Ast::binary_node(NodeKind::Mul, l_ast, r_ast, pos, line_num)?;
+                        },
+                        match_token!(TokenKind::Slash, pos, line_num) => {
+                            // unary ("*" unary | "/" unary)
+                            //                     ^
+                            let r_ast = Ast::unary(tokens, variable_list)?;
+                            // unary ("*" unary | "/" unary)
+                            //                            ^
+                            l_ast = Ast::binary_node(NodeKind::Div, l_ast, r_ast, pos, line_num)?;
+                        },
+                        _ => unreachable!(),
+                    }
+                },
+                _ => return Ok(l_ast)
+            }
+        }
+    }
+
+    fn unary<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        //   ("+" | "-")? primary
+        //  ^
+        match tokens.peek().unwrap().val {
+            TokenKind::Plus | TokenKind::Minus => {
+                match tokens.next().unwrap() {
+                    //   ("+" | "-")? primary
+                    //     ^
+                    match_token_ok!(TokenKind::Plus) => Ast::unary(tokens, variable_list),
+                    //   ("+" | "-")? primary
+                    //           ^
+                    match_token!(TokenKind::Minus, pos, line_num) => {
+                        let l_ast = Ast::num(0);
+                        let r_ast = Ast::unary(tokens, variable_list)?;
+                    //   ("+" | "-")? primary
+                    //                      ^
+                        Ast::sub_node(l_ast, r_ast, pos, line_num)
+                    },
+                    _ => unreachable!(),
+                }
+            },
+            TokenKind::Asterisk | TokenKind::Ampersand => {
+                match tokens.next().unwrap() {
+                    match_token!(TokenKind::Asterisk, pos, line_num) => {
+                        let hs = Ast::unary(tokens, variable_list)?;
+                        match hs.ty().base() {
+                            None => Err(AstError::not_pointer(pos, line_num)),
+                            // 関数へのポインタの参照先は関数名と同じくポインタのまま扱う
+                            Some(Type::Func(_)) => Ok(hs),
+                            Some(_) => Ok(Ast::deref_node(hs)),
+                        }
+                    },
+                    match_token_ok!(TokenKind::Ampersand) => {
+                        let hs = Ast::unary(tokens, variable_list)?;
+                        if hs.func_name().is_some() {
+                            return Ok(hs);
+                        }
+                        Ok(Ast::addr_node(hs))
+                    },
+                    _ => unreachable!(),
+                }
+            },
+            // sizeofはコンパイル時に数値へ置き換える
+            TokenKind::Sizeof => {
+                tokens.next();
+                // 不完全型の大きさは求められない
+                let Token { pos, line_num, .. } = *tokens.peek().unwrap();
+                match tokens.peek().unwrap() {
+                    match_token_ok!(TokenKind::LParen) => {
+                        tokens.next();
+                        // "sizeof" "(" type_name ")" | "sizeof" "(" expr ")"
+                        //               ^
+                        let ty = if Ast::is_type_name(tokens.peek().unwrap(), variable_list) {
+                            Ast::type_name(tokens, variable_list)?
+                        } else {
+                            Ast::expr(tokens, variable_list)?.ty()
+                        };
+                        if ty.is_incomplete() {
+                            return Err(AstError::incomplete_type(pos, line_num));
+                        }
+                        match tokens.next().unwrap() {
+                            match_token_ok!(TokenKind::RParen) => Ok(Ast::num(ty.size() as u64)),
+                            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
+                        }
+                    },
+                    _ => {
+                        let hs = Ast::unary(tokens, variable_list)?;
+                        if hs.ty().is_incomplete() {
+                            return Err(AstError::incomplete_type(pos, line_num));
+                        }
+                        Ok(Ast::num(hs.ty().size() as u64))
+                    }
+                }
+            },
+            // "(" type_name ")" unary | "(" expr ")"
+            // "("の次のトークンが型名かどうかでキャストと括弧の式を区別する
+            TokenKind::LParen => {
+                tokens.next();
+                if !Ast::is_type_name(tokens.peek().unwrap(), variable_list) {
+                    let ex = Ast::expr(tokens, variable_list)?;
+                    return match tokens.next().unwrap() {
+                        match_token_ok!(TokenKind::RParen) => Ast::postfix_suffix(tokens, variable_list, ex),
+                        match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
+                    };
+                }
+                let ty = Ast::type_name(tokens, variable_list)?;
+                match tokens.next().unwrap() {
+                    match_token!(TokenKind::RParen, pos, line_num) => {
+                        let hs = Ast::unary(tokens, variable_list)?;
+                        // voidへのキャストは値を捨てる
+                        if *ty.unqual() == Type::Void {
+                            return Ok(Ast::cast_node(ty, hs));
+                        }
+                        // 変換できるのはスカラー型(算術型とポインタ)同士のみ
+                        let hs = Ast::value(hs, pos, line_num)?;
+                        let from = hs.ty();
+                        let is_scalar = |ty: &Type| ty.is_numeric() || matches!(ty.unqual(), Type::Ptr(_));
+                        if !is_scalar(&ty) || !is_scalar(&from.decay()) {
+                            return Err(AstError::invalid_operand(pos, line_num));
+                        }
+                        Ok(Ast::cast_node(ty, hs))
+                    },
+                    match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
+                }
+            },
+            _ => Ast::postfix(tokens, variable_list),
+        }
+    }
+
+    fn postfix<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let l_ast = Ast::primary(tokens, variable_list)?;
+        Ast::postfix_suffix(tokens, variable_list, l_ast)
+    }
+
+    // ("[" expr "]" | "." ident | "->" ident | "(" (assign ",")* assign? ")")*
+    fn postfix_suffix<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, mut l_ast: Ast) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        loop {
+            match tokens.peek().unwrap() {
+                // a[i]は*(a + i)として扱う
+                match_token_ok!(TokenKind::LSquareBra) => {
+                    let Token { pos, line_num, .. } = tokens.next().unwrap();
+                    let index = Ast::expr(tokens, variable_list)?;
+                    match tokens.next().unwrap() {
+                        match_token_ok!(TokenKind::RSquareBra) => {
+                            let addr = Ast::add_node(l_ast, index, pos, line_num)?;
+                            if addr.ty().base().is_none() {
+                                return Err(AstError::not_pointer(pos, line_num));
+                            }
+                            l_ast = Ast::deref_node(addr);
+                        },
+                        match_token_nothing!(pos, line_num) => return Err(AstError::unclosed_square_bracket(pos, line_num)),
+                    }
+                },
+                match_token_ok!(TokenKind::Dot) => {
+                    let Token { pos, line_num, .. } = tokens.next().unwrap();
+                    l_ast = Ast::member_node(tokens, l_ast, pos, line_num)?;
+                },
+                // p->aは(*p).aとして扱う
+                match_token_ok!(TokenKind::Arrow) => {
+                    let Token { pos, line_num, .. } = tokens.next().unwrap();
+                    if l_ast.ty().base().is_none() {
+                        return Err(AstError::not_pointer(pos, line_num));
+                    }
+                    l_ast = Ast::member_node(tokens, Ast::deref_node(l_ast), pos, line_num)?;
+                },
+                match_token_ok!(TokenKind::LParen) => {
+                    let Token { pos, line_num, .. } = tokens.next().unwrap();
+                    l_ast = Ast::call(tokens, variable_list, l_ast, pos, line_num)?;
+                },
+                _ => return Ok(l_ast),
+            }
+        }
+    }
+
+    fn member_node<Tokens>(tokens: &mut Peekable<Tokens>, hs: Ast, pos: Location, line_num: usize) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        let struct_ref = match hs.ty() {
+            Type::Struct(struct_ref) => struct_ref,
+            _ => return Err(AstError::not_struct(pos, line_num)),
+        };
+        if !struct_ref.is_complete() {
+            return Err(AstError::incomplete_type(pos, line_num));
+        }
+        match tokens.next().unwrap() {
+            match_token_ident!(name, pos, line_num) => match struct_ref.member(&name) {
+                // constな構造体のメンバもconst
+                Some(member) => Ok(Ast::MemberNode {
+                    ty: member.ty.qualify(hs.object_ty().map(|ty| ty.qualifiers()).unwrap_or_default()),
+                    offset: member.offset,
+                    hs: Box::new(hs),
+                }),
+                None => Err(AstError::no_member(pos, line_num)),
+            },
+            match_token_nothing!(pos, line_num) => Err(AstError::require_ident(pos, line_num)),
+        }
+    }
+
+    // 関数呼び出しの引数をプロトタイプの引数の型に変換する
+    // 宣言のない関数はintを返すものとし, 引数には既定の実引数拡張を行う(floatはdoubleで渡す)
+    fn call<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, func: Ast, pos: Location, line_num: usize) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // postfix "(" (assign ",")* assign? ")"
+        //            ^
+        let mut argument_list = Vec::new();
+        if let match_token_ok!(TokenKind::RParen) = tokens.peek().unwrap() {
+            tokens.next();
+        } else {
+            loop {
+                let Token { pos, line_num, .. } = *tokens.peek().unwrap();
+                let arg = Ast::value(Ast::assign(tokens, variable_list)?, pos, line_num)?;
+                argument_list.push(arg);
+                match tokens.next().unwrap() {
+                    match_token_ok!(TokenKind::Comma) => continue,
+                    match_token_ok!(TokenKind::RParen) => break,
+                    match_token_nothing!(pos, line_num) => return Err(AstError::require_commma(pos, line_num)),
+                }
+            }
+        }
+        let (ret, params, is_variadic) = match func.ty().base() {
+            Some(Type::Func(func_ty)) => (func_ty.ret.clone(), func_ty.params.clone(), func_ty.is_variadic),
+            _ => return Err(AstError::not_pattern_matching(pos, line_num)),
+        };
+        let argument_list = match params {
+            Some(params) => {
+                // 可変長引数の関数は固定の引数より多く渡せる
+                if params.len() > argument_list.len() || (!is_variadic && params.len() < argument_list.len()) {
+                    return Err(AstError::argument_mismatch(pos, line_num));
+                }
+                let mut argument_list = argument_list.into_iter();
+                let mut args = Vec::new();
+                for (param, arg) in params.into_iter().zip(argument_list.by_ref()) {
+                    args.push(Ast::convert_argument(param, arg, pos, line_num)?);
+                }
+                args.extend(argument_list.map(Ast::default_promotion));
+                args
+            },
+            None => argument_list.into_iter().map(Ast::default_promotion).collect(),
+        };
+        // 構造体の返り値は呼び出し元の一時領域に受け取る
+        let ret_buf = match ret.is_struct() {
+            true => Some(variable_list.alloc_temp(&ret)),
+            false => None,
+        };
+        Ok(Ast::call_func_node(func, ret, argument_list, ret_buf))
+    }
+
+    // 既定の実引数拡張(floatはdoubleで渡す)
+    fn default_promotion(arg: Ast) -> Ast {
+        match arg.ty() {
+            Type::Float => Ast::cast_node(Type::Double, arg),
+            _ => arg,
+        }
+    }
+
+    // va_start(ap, last), va_arg(ap, type_name), va_copy(dest, src), va_end(ap)
+    // va_listは要素一つの配列なので, apは要素へのポインタとして扱う
+    fn va_builtin<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList, name: &str, pos: Location, line_num: usize) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        // name "(" assign ("," (assign | type_name))? ")"
+        //      ^
+        tokens.next();
+        let ap = Ast::assign(tokens, variable_list)?;
+        if ap.ty().base().is_none() {
+            return Err(AstError::not_pointer(pos, line_num));
+        }
+        if name != "va_end" {
+            match tokens.next().unwrap() {
+                match_token_ok!(TokenKind::Comma) => (),
+                match_token_nothing!(pos, line_num) => return Err(AstError::require_commma(pos, line_num)),
+            }
+        }
+        let res = match name {
+            // 最後の固定の引数は使わない
+            "va_start" => {
+                Ast::assign(tokens, variable_list)?;
+                let va_area = match variable_list.get("__va_area__") {
+                    Some(VarScope::Local(variable)) => Ast::Ident("__va_area__".to_string(), variable.offset, variable.ty.clone()),
+                    _ => return Err(AstError::not_pattern_matching(pos, line_num)),
+                };
+                Ast::node(NodeKind::Substitution, Ast::deref_node(ap), va_area)
+            },
+            "va_copy" => {
+                let src = Ast::assign(tokens, variable_list)?;
+                if src.ty().base().is_none() {
+                    return Err(AstError::not_pointer(pos, line_num));
+                }
+                Ast::node(NodeKind::Substitution, Ast::deref_node(ap), Ast::deref_node(src))
+            },
+            // 構造体はva_argで読み出せない
+            "va_arg" => {
+                let ty = Ast::type_name(tokens, variable_list)?;
+                if !ty.is_numeric() && ty.base().is_none() {
+                    return Err(AstError::invalid_operand(pos, line_num));
+                }
+                Ast::VaArgNode { ty: ty.unqual().clone(), hs: Box::new(ap) }
+            },
+            _ => Ast::cast_node(Type::Void, ap),
+        };
+        match tokens.next().unwrap() {
+            match_token_ok!(TokenKind::RParen) => Ok(res),
+            match_token_nothing!(pos, line_num) => Err(AstError::unclosed_parenth(pos, line_num)),
+        }
+    }
+
+    // 数値は代入と同じく変換し, ポインタは同じ型(配列, 0を含む)のみ受け付ける
+    fn convert_argument(param: Type, arg: Ast, pos: Location, line_num: usize) -> Result<Ast, AstError> {
+        let arg_ty = arg.ty();
+        match (&param, &arg_ty) {
+            (param, arg_ty) if param.is_numeric() && arg_ty.is_numeric() => Ok(Ast::cast_node(param.clone(), arg)),
+            (Type::Ptr(_), _) if param.discards_qualifiers(&arg_ty) => Err(AstError::discard_qualifiers(pos, line_num)),
+            (Type::Ptr(_), _) if arg_ty.base().is_some() => Ok(arg),
+            (Type::Ptr(_), _) if arg.eval() == Some(0) => Ok(arg),
+            (param, arg_ty) if param == arg_ty => Ok(arg),
+            _ => Err(AstError::argument_mismatch(pos, line_num)),
+        }
+    }
+
+    fn primary<Tokens>(tokens: &mut Peekable<Tokens>, variable_list: &mut VariableList) -> Result<Ast, AstError>
+    where
+        Tokens: Iterator<Item = Token>,
+    {
+        //  num | ident
+        // ^
+        match tokens.next().unwrap() {
+            match_token_num!(num, _pos, _line_num) => Ok(Ast::num(num)),
+            // uは32bitに収まらなければunsigned long, lはlongに収まらなければunsigned long
+            Token { val: TokenKind::SuffixedNum(num, is_unsigned, is_long), .. } => {
+                let ty = match (is_unsigned, is_long) {
+                    (true, false) if num <= u32::MAX as u64 => Type::UInt,
+                    (false, _) if num <= i64::MAX as u64 => Type::Long,
+                    _ => Type::ULong,
+                };
+                Ok(Ast::cast_node(ty, Ast::num(num)))
+            },
+            Token { val: TokenKind::Float(val), .. } => Ok(Ast::FNum(val, Type::Float)),
+            Token { val: TokenKind::Double(val), .. } => Ok(Ast::FNum(val, Type::Double)),
+            // 隣接する文字列リテラルは連結する
+            match_token_str!(data) => {
+                let mut data = data;
+                while let TokenKind::Str(_) = tokens.peek().unwrap().val {
+                    if let match_token_str!(next) = tokens.next().unwrap() {
+                        data.extend(next);
+                    }
+                }
+                Ok(variable_list.add_string_literal(data))
+            },
+            match_token_ident!(str, pos, line_num) => {
+                if let ("va_start" | "va_arg" | "va_copy" | "va_end", match_token_ok!(TokenKind::LParen)) = (str.as_str(), tokens.peek().unwrap()) {
+                    return Ast::va_builtin(tokens, variable_list, &str, pos, line_num);
+                }
+                // 内側のスコープで宣言されたものを優先する
+                match variable_list.get(&str) {
+                    Some(VarScope::Local(variable)) => Ok(Ast::Ident(str, variable.offset, variable.ty.clone())),
+                    Some(VarScope::Global(ty @ Type::Func(_))) => Ok(Ast::func_designator(str, ty.clone())),
+                    Some(VarScope::Global(ty)) => Ok(Ast::GlobalIdent(str, ty.clone())),
+                    Some(VarScope::StaticLocal(label, ty)) => Ok(Ast::GlobalIdent(label.clone(), ty.clone())),
+                    // 列挙子は定数として扱う
+                    Some(VarScope::EnumConstant(val)) => Ok(Ast::num(*val as u64)),
+                    Some(VarScope::Typedef(_)) => Err(AstError::not_pattern_matching(pos, line_num)),
+                    // 宣言のない関数はintを返すものとする
+                    None => match tokens.peek().unwrap() {
+                        match_token_ok!(TokenKind::LParen) => Ok(Ast::func_designator(str, Type::func(Type::Int, None, false))),
+                        _ => Err(AstError::undeclared_variable(pos, line_num)),
+                    },
+                }
+            },
+            match_token_nothing!(pos, line_num) => Err(AstError::not_pattern_matching(pos, line_num)),
+        }
+    }
+}
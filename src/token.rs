@@ -1,259 +1,709 @@
-use std::error;
-use std::fmt;
-
-#[derive(Debug, PartialEq)]
-pub enum TokenKind {
-    Num(u64),
-    Ident(String),
-    Plus,       // +
-    Minus,      // -
-    LParen,     // (
-    RParen,     // )
-    LCuryBra,   // {
-    RCuryBra,   // }
-    Asterisk,   // *
-    Slash,      // /
-    Large,      // >
-    Small,      // <
-    EqualSmall, // <=
-    EqualLarge, // >=
-    Equal,      // ==
-    NotEqual,   // !=
-    Substitution,   // =
-    SemiColon,  // ;
-    Comma,      // ,
-    Ampersand,  // &
-    Exclamation,    // !
-    Return,
-    If,
-    Else,
-    While,
-    For,
-    EOF,
-}
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum TokenizeErrorKind {
-    NotNumber,
-}
-
-#[derive(Debug, PartialEq)]
-pub struct TokenizeError {
-    val: TokenizeErrorKind,
-    pos: Location,
-    str: String,
-}
-
-impl TokenizeError {
-    fn new(val: TokenizeErrorKind, pos: Location, str: String) -> Self {
-        Self {
-            val,
-            pos,
-            str
-        }
-    }
-
-    pub fn not_number(pos: Location, original_code: String) -> Self {
-        Self::new(TokenizeErrorKind::NotNumber, pos, original_code)
-    }
-}
-
-impl fmt::Display for TokenizeError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use TokenizeErrorKind::*;
-        let space = " ".repeat(self.pos.0);
-        let hat = "^".repeat(self.pos.1 - self.pos.0 + 1);
-        match self.val {
-            NotNumber => write!(f, "{}\n{}{} NotNumber", self.str, space, hat),
-        }
-    }
-}
-
-impl error::Error for TokenizeError {}
-
-#[derive(Debug, Copy, Clone, PartialEq
-)]
-pub struct Location(pub usize, pub usize);
-
-#[derive(Debug, PartialEq)]
-pub struct Token {
-    pub val: TokenKind,
-    pub pos: Location,
-    pub line_num : usize
-}
-
-impl Token {
-    pub fn new(val: TokenKind, pos: Location, line_num: usize) -> Self {
-        Token {
-            val,
-            pos,
-            line_num
-        }
-    }
-
-    pub fn tokenize(str: &[u8], line_num: usize) -> Result<Vec<Token>, TokenizeError> {
-        let mut result: Vec<Token> = Vec::new();
-        let mut pos = 0;
-
-        while pos < str.len() {
-            match str[pos] {
-                b' ' | b'\t' | b'\n' => pos += 1,
-                b'+' => {result.push(Token::new(TokenKind::Plus, Location(pos, pos), line_num)); pos += 1;},
-                b'-' => {result.push(Token::new(TokenKind::Minus, Location(pos, pos), line_num)); pos += 1;},
-                b')' => {result.push(Token::new(TokenKind::RParen, Location(pos, pos), line_num)); pos += 1;},
-                b'(' => {result.push(Token::new(TokenKind::LParen, Location(pos, pos), line_num)); pos += 1;},
-                b'{' => {result.push(Token::new(TokenKind::LCuryBra, Location(pos, pos), line_num)); pos += 1;},
-                b'}' => {result.push(Token::new(TokenKind::RCuryBra, Location(pos, pos), line_num)); pos += 1;},
-                b'*' => {result.push(Token::new(TokenKind::Asterisk, Location(pos, pos), line_num)); pos += 1;},
-                b'&' => {result.push(Token::new(TokenKind::Ampersand, Location(pos, pos), line_num)); pos += 1;},
-                b'/' => {result.push(Token::new(TokenKind::Slash, Location(pos, pos), line_num)); pos += 1;},
-                b'<' => {
-                    let start = pos;
-                    pos += 1;
-                    if pos == str.len() {result.push(Token::new(TokenKind::Small, Location(start, pos - 1), line_num)); break;}
-                    match str[pos] {
-                        b'=' => {result.push(Token::new(TokenKind::EqualSmall, Location(start, pos), line_num)); pos += 1;},
-                        _ => result.push(Token::new(TokenKind::Small, Location(start, pos), line_num)),
-                    }
-                },
-                b'>' => {
-                    let start = pos;
-                    pos += 1;
-                    if pos == str.len() {result.push(Token::new(TokenKind::Large, Location(start, pos - 1), line_num)); break;}
-                    match str[pos] {
-                        b'=' => {result.push(Token::new(TokenKind::EqualLarge, Location(start, pos), line_num)); pos += 1;},
-                        _ => result.push(Token::new(TokenKind::Large, Location(start, pos), line_num)),
-                    }
-                },
-                b'=' => {
-                    let start = pos;
-                    pos += 1;
-                    if pos == str.len() {result.push(Token::new(TokenKind::Substitution, Location(start, pos - 1), line_num)); break;}
-                    match str[pos] {
-                        b'=' => {result.push(Token::new(TokenKind::Equal, Location(start, pos), line_num)); pos += 1;},
-                        _ => result.push(Token::new(TokenKind::Substitution, Location(start, pos), line_num)),
-                    }
-                },
-                b'!' => {
-                    let start = pos;
-                    pos += 1;
-                    if pos == str.len() {result.push(Token::new(TokenKind::Exclamation, Location(start, pos - 1), line_num));break;}
-                    match str[pos] {
-                        b'=' => {result.push(Token::new(TokenKind::NotEqual, Location(start, pos), line_num)); pos += 1;},
-                        _ => result.push(Token::new(TokenKind::Exclamation, Location(start, pos), line_num))
-                    }
-                },
-                b';' => {result.push(Token::new(TokenKind::SemiColon, Location(pos, pos), line_num)); pos += 1;},
-                b',' => {result.push(Token::new(TokenKind::Comma, Location(pos, pos), line_num)); pos += 1;},
-                b'0'..=b'9' => {
-                    let (num, new_pos) = Token::tokenize_number(str, pos)?;
-                    let token = Token::new(TokenKind::Num(num), Location(pos, new_pos - 1), line_num);
-                    result.push(token);
-                    pos = new_pos;
-                },
-                _ => {
-                    let (ident, new_pos) = Token::tokenize_ident(&str, pos);
-                    match &ident[..] {
-                        "if" => {
-                            result.push(Token::new(TokenKind::If, Location(pos, new_pos - 1), line_num));
-                        },
-                        "else" => {
-                            result.push(Token::new(TokenKind::Else, Location(pos, new_pos - 1), line_num));
-                        },
-                        "while" => {
-                            result.push(Token::new(TokenKind::While, Location(pos, new_pos - 1), line_num));
-                        },
-                        "for" => {
-                            result.push(Token::new(TokenKind::For, Location(pos, new_pos - 1), line_num));
-                        },
-                        "return" => {
-                            result.push(Token::new(TokenKind::Return, Location(pos, new_pos - 1), line_num));
-                        },
-                        _ => result.push(Token::new(TokenKind::Ident(ident), Location(pos, new_pos - 1), line_num))
-                    }
-                    pos = new_pos
-                }
-            }
-        }
-        Ok(result)
-    }
-
-    fn tokenize_number(input: &[u8], mut pos: usize) -> Result<(u64, usize) , TokenizeError> {
-        let start = pos;
-        while pos < input.len() && !b" \t\n+-(){}*/><=!;.&".contains(&input[pos]) {
-            pos += 1;
-        }
-        let num_str = String::from_utf8(input[start..pos].to_vec())
-                    .unwrap()
-                    .parse::<u64>();
-        match num_str {
-            Ok(num) => Ok((num, pos)),
-            Err(_) => Err(TokenizeError::not_number(Location(start, pos -1), input[start..pos].iter().map(|&c| c as char).collect::<String>())),
-        }
-    }
-
-    fn tokenize_ident(input: &[u8], mut pos: usize) -> (String, usize) {
-        let start = pos;
-        while pos < input.len() && !b" \t\n+-(){}*/><=!;.&".contains(&input[pos]) {
-            pos += 1;
-        }
-        // Used in utf-8??
-        let ident = String::from_utf8(input[start..pos].to_vec())
-                    .unwrap()
-                    .parse()
-                    .unwrap();
-        (ident, pos)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_tokenize_number() {
-        let input = &"098".as_bytes();
-        assert_eq!(Token::tokenize_number(input, 0).unwrap(), (98, 3));
-
-        let input = &"012notnum".as_bytes();
-        assert_eq!(Token::tokenize_number(input, 0), Err(TokenizeError::not_number(Location(0, 8), String::from("012notnum"))));
-
-        let input = &"012+abc".as_bytes();
-        assert_eq!(Token::tokenize_number(input, 0), Ok((12, 3)));
-    }
-
-    #[test]
-    fn test_tokenize_error_diplay() {
-        let tokenize_error = TokenizeError::not_number(Location(4, 5), String::from("a + 0a"));
-        assert_eq!(format!("{:#}", tokenize_error), 
-            String::from("a + 0a\n    ^^ NotNumber"))
-    }
-
-    #[test]
-    fn test_tokenize_ident() {
-        let input = "abcd".as_bytes();
-        assert_eq!(Token::tokenize_ident(input, 0), ("abcd".to_string(), 4));
-
-        let input = "abcd.efg".as_bytes();
-        assert_eq!(Token::tokenize_ident(input, 0), ("abcd".to_string(), 4))
-    }
-
-    #[test]
-    fn test_tokenize() {
-        let input = "<=".as_bytes();
-        assert_eq!(Token::tokenize(input, 0), Ok(vec![Token::new(TokenKind::EqualSmall, Location(0, 1), 0)]));
-
-        let input = "<".as_bytes();
-        assert_eq!(Token::tokenize(input, 0), Ok(vec![Token::new(TokenKind::Small, Location(0, 0), 0)]));
-
-        let input = "ifa".as_bytes();
-        assert_eq!(Token::tokenize(input, 0), Ok(vec![
-            Token::new(TokenKind::Ident("ifa".to_string()), Location(0, 2), 0)
-        ]));
-
-        let input = "if 0123".as_bytes();
-        assert_eq!(Token::tokenize(input, 0), Ok(vec![
-            Token::new(TokenKind::If, Location(0, 1), 0),
-            Token::new(TokenKind::Num(123), Location(3, 6), 0)
-        ]))
-    }
-}
+use std::error;
+use std::fmt;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub enum TokenKind {
+    Num(u64),
+    SuffixedNum(u64, bool, bool),   // u, lサフィックス付きの整数(値, 符号なしか, longか)
+    Float(f64),
+    Double(f64),
+    Ident(String),
+    Str(Vec<u8>),
+    Plus,       // +
+    Minus,      // -
+    LParen,     // (
+    RParen,     // )
+    LCuryBra,   // {
+    RCuryBra,   // }
+    LSquareBra, // [
+    RSquareBra, // ]
+    Asterisk,   // *
+    Slash,      // /
+    Large,      // >
+    Small,      // <
+    EqualSmall, // <=
+    EqualLarge, // >=
+    ShiftLeft,  // <<
+    ShiftRight, // >>
+    Equal,      // ==
+    NotEqual,   // !=
+    Substitution,   // =
+    SemiColon,  // ;
+    Comma,      // ,
+    Dot,        // .
+    Ellipsis,   // ...
+    Arrow,      // ->
+    Ampersand,  // &
+    Exclamation,    // !
+    Return,
+    If,
+    Else,
+    While,
+    For,
+    Int,
+    Char,
+    Short,
+    Long,
+    Signed,
+    Unsigned,
+    Bool,
+    Void,
+    FloatType,
+    DoubleType,
+    Struct,
+    Union,
+    Enum,
+    Sizeof,
+    Typedef,
+    Static,
+    Extern,
+    Const,
+    Volatile,
+    EOF,
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenizeErrorKind {
+    NotNumber,
+    UnclosedString,
+    InvalidChar,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TokenizeError {
+    val: TokenizeErrorKind,
+    pos: Location,
+    str: String,
+}
+
+impl TokenizeError {
+    fn new(val: TokenizeErrorKind, pos: Location, str: String) -> Self {
+        Self {
+            val,
+            pos,
+            str
+        }
+    }
+
+    pub fn not_number(pos: Location, original_code: String) -> Self {
+        Self::new(TokenizeErrorKind::NotNumber, pos, original_code)
+    }
+
+    pub fn unclosed_string(pos: Location, original_code: String) -> Self {
+        Self::new(TokenizeErrorKind::UnclosedString, pos, original_code)
+    }
+
+    pub fn invalid_char(pos: Location, original_code: String) -> Self {
+        Self::new(TokenizeErrorKind::InvalidChar, pos, original_code)
+    }
+}
+
+impl fmt::Display for TokenizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TokenizeErrorKind::*;
+        let space = " ".repeat(self.pos.0);
+        let hat = "^".repeat(self.pos.1 - self.pos.0 + 1);
+        match self.val {
+            NotNumber => write!(f, "{}\n{}{} NotNumber", self.str, space, hat),
+            UnclosedString => write!(f, "{}\n{}{} UnclosedString", self.str, space, hat),
+            InvalidChar => write!(f, "{}\n{}{} InvalidChar", self.str, space, hat),
+        }
+    }
+}
+
+impl error::Error for TokenizeError {}
+
+#[derive(Debug, Copy, Clone, PartialEq
+)]
+pub struct Location(pub usize, pub usize);
+
+#[derive(Debug, PartialEq)]
+pub struct Token {
+    pub val: TokenKind,
+    pub pos: Location,
+    pub line_num : usize
+}
+
+impl Token {
+    pub fn new(val: TokenKind, pos: Location, line_num: usize) -> Self {
+        Token {
+            val,
+            pos,
+            line_num
+        }
+    }
+
+    pub fn tokenize(str: &[u8], line_num: usize) -> Result<Vec<Token>, TokenizeError> {
+        let mut result: Vec<Token> = Vec::new();
+        let mut pos = 0;
+
+        while pos < str.len() {
+            match str[pos] {
+                b' ' | b'\t' | b'\n' => pos += 1,
+                b'+' => {result.push(Token::new(TokenKind::Plus, Location(pos, pos), line_num)); pos += 1;},
+                b'-' => {
+                    let start = pos;
+                    pos += 1;
+                    if pos == str.len() {result.push(Token::new(TokenKind::Minus, Location(start, pos - 1), line_num)); break;}
+                    match str[pos] {
+                        b'>' => {result.push(Token::new(TokenKind::Arrow, Location(start, pos), line_num)); pos += 1;},
+                        _ => result.push(Token::new(TokenKind::Minus, Location(start, start), line_num)),
+                    }
+                },
+                b')' => {result.push(Token::new(TokenKind::RParen, Location(pos, pos), line_num)); pos += 1;},
+                b'(' => {result.push(Token::new(TokenKind::LParen, Location(pos, pos), line_num)); pos += 1;},
+                b'{' => {result.push(Token::new(TokenKind::LCuryBra, Location(pos, pos), line_num)); pos += 1;},
+                b'}' => {result.push(Token::new(TokenKind::RCuryBra, Location(pos, pos), line_num)); pos += 1;},
+                b'[' => {result.push(Token::new(TokenKind::LSquareBra, Location(pos, pos), line_num)); pos += 1;},
+                b']' => {result.push(Token::new(TokenKind::RSquareBra, Location(pos, pos), line_num)); pos += 1;},
+                b'*' => {result.push(Token::new(TokenKind::Asterisk, Location(pos, pos), line_num)); pos += 1;},
+                b'&' => {result.push(Token::new(TokenKind::Ampersand, Location(pos, pos), line_num)); pos += 1;},
+                b'/' => {result.push(Token::new(TokenKind::Slash, Location(pos, pos), line_num)); pos += 1;},
+                b'<' => {
+                    let start = pos;
+                    pos += 1;
+                    if pos == str.len() {result.push(Token::new(TokenKind::Small, Location(start, pos - 1), line_num)); break;}
+                    match str[pos] {
+                        b'=' => {result.push(Token::new(TokenKind::EqualSmall, Location(start, pos), line_num)); pos += 1;},
+                        b'<' => {result.push(Token::new(TokenKind::ShiftLeft, Location(start, pos), line_num)); pos += 1;},
+                        _ => result.push(Token::new(TokenKind::Small, Location(start, pos), line_num)),
+                    }
+                },
+                b'>' => {
+                    let start = pos;
+                    pos += 1;
+                    if pos == str.len() {result.push(Token::new(TokenKind::Large, Location(start, pos - 1), line_num)); break;}
+                    match str[pos] {
+                        b'=' => {result.push(Token::new(TokenKind::EqualLarge, Location(start, pos), line_num)); pos += 1;},
+                        b'>' => {result.push(Token::new(TokenKind::ShiftRight, Location(start, pos), line_num)); pos += 1;},
+                        _ => result.push(Token::new(TokenKind::Large, Location(start, pos), line_num)),
+                    }
+                },
+                b'=' => {
+                    let start = pos;
+                    pos += 1;
+                    if pos == str.len() {result.push(Token::new(TokenKind::Substitution, Location(start, pos - 1), line_num)); break;}
+                    match str[pos] {
+                        b'=' => {result.push(Token::new(TokenKind::Equal, Location(start, pos), line_num)); pos += 1;},
+                        _ => result.push(Token::new(TokenKind::Substitution, Location(start, pos), line_num)),
+                    }
+                },
+                b'!' => {
+                    let start = pos;
+                    pos += 1;
+                    if pos == str.len() {result.push(Token::new(TokenKind::Exclamation, Location(start, pos - 1), line_num));break;}
+                    match str[pos] {
+                        b'=' => {result.push(Token::new(TokenKind::NotEqual, Location(start, pos), line_num)); pos += 1;},
+                        _ => result.push(Token::new(TokenKind::Exclamation, Location(start, pos), line_num))
+                    }
+                },
+                b';' => {result.push(Token::new(TokenKind::SemiColon, Location(pos, pos), line_num)); pos += 1;},
+                b',' => {result.push(Token::new(TokenKind::Comma, Location(pos, pos), line_num)); pos += 1;},
+                // .5のような浮動小数点数
+                b'.' if str.get(pos + 1).is_some_and(|c| c.is_ascii_digit()) => {
+                    let (kind, new_pos) = Token::tokenize_number(str, pos)?;
+                    result.push(Token::new(kind, Location(pos, new_pos - 1), line_num));
+                    pos = new_pos;
+                },
+                b'.' if str[pos..].starts_with(b"...") => {result.push(Token::new(TokenKind::Ellipsis, Location(pos, pos + 2), line_num)); pos += 3;},
+                b'.' => {result.push(Token::new(TokenKind::Dot, Location(pos, pos), line_num)); pos += 1;},
+                b'"' => {
+                    let (bytes, new_pos) = Token::tokenize_string(str, pos)?;
+                    result.push(Token::new(TokenKind::Str(bytes), Location(pos, new_pos - 1), line_num));
+                    pos = new_pos;
+                },
+                // 文字リテラルは数値として扱う
+                b'\'' => {
+                    let (num, new_pos) = Token::tokenize_char(str, pos)?;
+                    result.push(Token::new(TokenKind::Num(num), Location(pos, new_pos - 1), line_num));
+                    pos = new_pos;
+                },
+                b'0'..=b'9' => {
+                    let (kind, new_pos) = Token::tokenize_number(str, pos)?;
+                    let token = Token::new(kind, Location(pos, new_pos - 1), line_num);
+                    result.push(token);
+                    pos = new_pos;
+                },
+                _ => {
+                    let (ident, new_pos) = Token::tokenize_ident(str, pos);
+                    // 識別子にならない文字で止まらないようにする
+                    if new_pos == pos {
+                        return Err(TokenizeError::invalid_char(Location(pos, pos), str.iter().map(|&c| c as char).collect::<String>()));
+                    }
+                    match &ident[..] {
+                        "if" => {
+                            result.push(Token::new(TokenKind::If, Location(pos, new_pos - 1), line_num));
+                        },
+                        "else" => {
+                            result.push(Token::new(TokenKind::Else, Location(pos, new_pos - 1), line_num));
+                        },
+                        "while" => {
+                            result.push(Token::new(TokenKind::While, Location(pos, new_pos - 1), line_num));
+                        },
+                        "for" => {
+                            result.push(Token::new(TokenKind::For, Location(pos, new_pos - 1), line_num));
+                        },
+                        "return" => {
+                            result.push(Token::new(TokenKind::Return, Location(pos, new_pos - 1), line_num));
+                        },
+                        "int" => {
+                            result.push(Token::new(TokenKind::Int, Location(pos, new_pos - 1), line_num));
+                        },
+                        "char" => {
+                            result.push(Token::new(TokenKind::Char, Location(pos, new_pos - 1), line_num));
+                        },
+                        "short" => {
+                            result.push(Token::new(TokenKind::Short, Location(pos, new_pos - 1), line_num));
+                        },
+                        "long" => {
+                            result.push(Token::new(TokenKind::Long, Location(pos, new_pos - 1), line_num));
+                        },
+                        "signed" => {
+                            result.push(Token::new(TokenKind::Signed, Location(pos, new_pos - 1), line_num));
+                        },
+                        "unsigned" => {
+                            result.push(Token::new(TokenKind::Unsigned, Location(pos, new_pos - 1), line_num));
+                        },
+                        "_Bool" => {
+                            result.push(Token::new(TokenKind::Bool, Location(pos, new_pos - 1), line_num));
+                        },
+                        "void" => {
+                            result.push(Token::new(TokenKind::Void, Location(pos, new_pos - 1), line_num));
+                        },
+                        "float" => {
+                            result.push(Token::new(TokenKind::FloatType, Location(pos, new_pos - 1), line_num));
+                        },
+                        "double" => {
+                            result.push(Token::new(TokenKind::DoubleType, Location(pos, new_pos - 1), line_num));
+                        },
+                        "struct" => {
+                            result.push(Token::new(TokenKind::Struct, Location(pos, new_pos - 1), line_num));
+                        },
+                        "union" => {
+                            result.push(Token::new(TokenKind::Union, Location(pos, new_pos - 1), line_num));
+                        },
+                        "enum" => {
+                            result.push(Token::new(TokenKind::Enum, Location(pos, new_pos - 1), line_num));
+                        },
+                        "sizeof" => {
+                            result.push(Token::new(TokenKind::Sizeof, Location(pos, new_pos - 1), line_num));
+                        },
+                        "typedef" => {
+                            result.push(Token::new(TokenKind::Typedef, Location(pos, new_pos - 1), line_num));
+                        },
+                        "static" => {
+                            result.push(Token::new(TokenKind::Static, Location(pos, new_pos - 1), line_num));
+                        },
+                        "extern" => {
+                            result.push(Token::new(TokenKind::Extern, Location(pos, new_pos - 1), line_num));
+                        },
+                        "const" => {
+                            result.push(Token::new(TokenKind::Const, Location(pos, new_pos - 1), line_num));
+                        },
+                        "volatile" => {
+                            result.push(Token::new(TokenKind::Volatile, Location(pos, new_pos - 1), line_num));
+                        },
+                        _ => result.push(Token::new(TokenKind::Ident(ident), Location(pos, new_pos - 1), line_num))
+                    }
+                    pos = new_pos
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    // 英数字, '.', 指数部の符号(e+, p-など)が続く範囲を一つの数として読む
+    fn tokenize_number(input: &[u8], mut pos: usize) -> Result<(TokenKind, usize) , TokenizeError> {
+        let start = pos;
+        while pos < input.len() {
+            let c = input[pos];
+            let is_exponent_sign = (c == b'+' || c == b'-') && b"eEpP".contains(&input[pos - 1]);
+            if !(c.is_ascii_alphanumeric() || c == b'.' || c == b'_' || is_exponent_sign) {
+                break;
+            }
+            pos += 1;
+        }
+        let num_str = String::from_utf8(input[start..pos].to_vec()).unwrap();
+        match Token::parse_number(&num_str) {
+            Some(kind) => Ok((kind, pos)),
+            None => Err(TokenizeError::not_number(Location(start, pos -1), input[start..pos].iter().map(|&c| c as char).collect::<String>())),
+        }
+    }
+
+    // 整数(10進数, 16進数)と浮動小数点数(10進数, 16進数)
+    // 整数はu, l, llサフィックス(順不同)で型を指定できる
+    // 浮動小数点数はfサフィックスならfloat, それ以外はdouble
+    fn parse_number(num_str: &str) -> Option<TokenKind> {
+        let hex = num_str.strip_prefix("0x").or_else(|| num_str.strip_prefix("0X"));
+        let is_flonum = match hex {
+            Some(hex) => hex.contains(&['p', 'P'][..]),
+            None => num_str.contains(&['.', 'e', 'E'][..]),
+        };
+        if !is_flonum {
+            let body = num_str.trim_end_matches(&['u', 'U', 'l', 'L'][..]);
+            let (is_unsigned, is_long) = match num_str[body.len()..].to_ascii_lowercase().as_str() {
+                "" => (false, false),
+                "u" => (true, false),
+                "l" | "ll" => (false, true),
+                "ul" | "lu" | "ull" | "llu" => (true, true),
+                _ => return None,
+            };
+            let val = match hex {
+                Some(_) => u64::from_str_radix(&body[2..], 16).ok()?,
+                None => body.parse::<u64>().ok()?,
+            };
+            return match (is_unsigned, is_long) {
+                (false, false) => Some(TokenKind::Num(val)),
+                _ => Some(TokenKind::SuffixedNum(val, is_unsigned, is_long)),
+            };
+        }
+        let (body, is_float) = match num_str.as_bytes()[num_str.len() - 1] {
+            b'f' | b'F' => (&num_str[..num_str.len() - 1], true),
+            b'l' | b'L' => (&num_str[..num_str.len() - 1], false),
+            _ => (num_str, false),
+        };
+        let val = match hex {
+            Some(_) => Token::parse_hex_float(&body[2..])?,
+            None => body.parse::<f64>().ok()?,
+        };
+        if is_float {
+            Some(TokenKind::Float(val as f32 as f64))
+        } else {
+            Some(TokenKind::Double(val))
+        }
+    }
+
+    // 0x1.8p3 = 0x18 * 2^(3 - 4)のように仮数部を整数として読み, 小数点以下の桁数だけ指数を減らす
+    fn parse_hex_float(body: &str) -> Option<f64> {
+        let exponent_pos = body.find(&['p', 'P'][..])?;
+        let mut exponent = body[exponent_pos + 1..].parse::<i32>().ok()?;
+        let mut mantissa: f64 = 0.0;
+        let mut is_fraction = false;
+        let mut digit_num = 0;
+        for c in body[..exponent_pos].chars() {
+            if c == '.' && !is_fraction {
+                is_fraction = true;
+                continue;
+            }
+            mantissa = mantissa * 16.0 + c.to_digit(16)? as f64;
+            digit_num += 1;
+            if is_fraction {
+                exponent -= 4;
+            }
+        }
+        if digit_num == 0 {
+            return None;
+        }
+        Some(mantissa * 2f64.powi(exponent))
+    }
+
+    // "..."の中身をエスケープシーケンスを展開して読み取る
+    // posは開始の"を指す
+    fn tokenize_string(input: &[u8], mut pos: usize) -> Result<(Vec<u8>, usize), TokenizeError> {
+        let start = pos;
+        let mut bytes = Vec::new();
+        pos += 1;
+        loop {
+            match input.get(pos) {
+                Some(b'"') => return Ok((bytes, pos + 1)),
+                Some(b'\\') => {
+                    let (byte, new_pos) = Token::read_escape(input, pos + 1)
+                        .ok_or_else(|| Token::unclosed_string(input, start))?;
+                    bytes.push(byte);
+                    pos = new_pos;
+                },
+                Some(&byte) => {
+                    bytes.push(byte);
+                    pos += 1;
+                },
+                None => return Err(Token::unclosed_string(input, start)),
+            }
+        }
+    }
+
+    fn tokenize_char(input: &[u8], pos: usize) -> Result<(u64, usize), TokenizeError> {
+        let (byte, new_pos) = match input.get(pos + 1) {
+            Some(b'\\') => Token::read_escape(input, pos + 2).ok_or_else(|| Token::unclosed_string(input, pos))?,
+            Some(b'\'') | None => return Err(Token::unclosed_string(input, pos)),
+            Some(&byte) => (byte, pos + 2),
+        };
+        match input.get(new_pos) {
+            // charは符号付きなので0x80以上は負の値になる
+            Some(b'\'') => Ok((byte as i8 as i64 as u64, new_pos + 1)),
+            _ => Err(Token::unclosed_string(input, pos)),
+        }
+    }
+
+    // posは\\の次の文字を指す
+    fn read_escape(input: &[u8], mut pos: usize) -> Option<(u8, usize)> {
+        match *input.get(pos)? {
+            // \ooo (8進数は3桁まで)
+            b'0'..=b'7' => {
+                let mut val: u32 = 0;
+                let start = pos;
+                while pos < input.len() && pos < start + 3 && (b'0'..=b'7').contains(&input[pos]) {
+                    val = val * 8 + (input[pos] - b'0') as u32;
+                    pos += 1;
+                }
+                Some((val as u8, pos))
+            },
+            // \xhh (16進数は続く限り読む)
+            b'x' => {
+                pos += 1;
+                let mut val: u32 = 0;
+                let start = pos;
+                while pos < input.len() && input[pos].is_ascii_hexdigit() {
+                    val = val.wrapping_mul(16) + (input[pos] as char).to_digit(16).unwrap();
+                    pos += 1;
+                }
+                if pos == start {
+                    return None;
+                }
+                Some((val as u8, pos))
+            },
+            c => {
+                let byte = match c {
+                    b'a' => 0x07,
+                    b'b' => 0x08,
+                    b't' => b'\t',
+                    b'n' => b'\n',
+                    b'v' => 0x0b,
+                    b'f' => 0x0c,
+                    b'r' => b'\r',
+                    b'e' => 0x1b,
+                    c => c,
+                };
+                Some((byte, pos + 1))
+            },
+        }
+    }
+
+    fn unclosed_string(input: &[u8], start: usize) -> TokenizeError {
+        TokenizeError::unclosed_string(Location(start, input.len() - 1), input.iter().map(|&c| c as char).collect::<String>())
+    }
+
+    // 英数字, _, utf-8のマルチバイト文字を識別子として読む
+    fn tokenize_ident(input: &[u8], mut pos: usize) -> (String, usize) {
+        let start = pos;
+        while pos < input.len() && (input[pos].is_ascii_alphanumeric() || input[pos] == b'_' || input[pos] >= 0x80) {
+            pos += 1;
+        }
+        // Used in utf-8??
+        let ident = String::from_utf8(input[start..pos].to_vec())
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+        (ident, pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_number() {
+        let input = &"098".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0).unwrap(), (TokenKind::Num(98), 3));
+
+        let input = &"012notnum".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Err(TokenizeError::not_number(Location(0, 8), String::from("012notnum"))));
+
+        let input = &"012+abc".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Ok((TokenKind::Num(12), 3)));
+
+        let input = &"0x1F;".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Ok((TokenKind::Num(31), 4)));
+
+        let input = &"1u 10L 0xFFul 7LLU".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Ok((TokenKind::SuffixedNum(1, true, false), 2)));
+        assert_eq!(Token::tokenize_number(input, 3), Ok((TokenKind::SuffixedNum(10, false, true), 6)));
+        assert_eq!(Token::tokenize_number(input, 7), Ok((TokenKind::SuffixedNum(255, true, true), 13)));
+        assert_eq!(Token::tokenize_number(input, 14), Ok((TokenKind::SuffixedNum(7, true, true), 18)));
+
+        let input = &"1uu".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Err(TokenizeError::not_number(Location(0, 2), String::from("1uu"))));
+    }
+
+    #[test]
+    fn test_tokenize_flonum() {
+        let input = &"1.5+2".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Ok((TokenKind::Double(1.5), 3)));
+
+        let input = &"2.5e-1f)".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Ok((TokenKind::Float(0.25), 7)));
+
+        let input = &"1e3".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Ok((TokenKind::Double(1000.0), 3)));
+
+        let input = &"0x1.8p1".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Ok((TokenKind::Double(3.0), 7)));
+
+        let input = &"0XAp-2F".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Ok((TokenKind::Float(2.5), 7)));
+
+        let input = &"1.2.3".as_bytes();
+        assert_eq!(Token::tokenize_number(input, 0), Err(TokenizeError::not_number(Location(0, 4), String::from("1.2.3"))));
+
+        let input = "(.5).x".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::LParen, Location(0, 0), 0),
+            Token::new(TokenKind::Double(0.5), Location(1, 2), 0),
+            Token::new(TokenKind::RParen, Location(3, 3), 0),
+            Token::new(TokenKind::Dot, Location(4, 4), 0),
+            Token::new(TokenKind::Ident("x".to_string()), Location(5, 5), 0),
+        ]));
+    }
+
+    #[test]
+    fn test_tokenize_error_diplay() {
+        let tokenize_error = TokenizeError::not_number(Location(4, 5), String::from("a + 0a"));
+        assert_eq!(format!("{:#}", tokenize_error), 
+            String::from("a + 0a\n    ^^ NotNumber"))
+    }
+
+    #[test]
+    fn test_tokenize_ident() {
+        let input = "abcd".as_bytes();
+        assert_eq!(Token::tokenize_ident(input, 0), ("abcd".to_string(), 4));
+
+        let input = "abcd.efg".as_bytes();
+        assert_eq!(Token::tokenize_ident(input, 0), ("abcd".to_string(), 4));
+
+        let input = "a,b".as_bytes();
+        assert_eq!(Token::tokenize_ident(input, 0), ("a".to_string(), 1));
+
+        let input = "a_1->b".as_bytes();
+        assert_eq!(Token::tokenize_ident(input, 0), ("a_1".to_string(), 3));
+
+        let input = ".a".as_bytes();
+        assert_eq!(Token::tokenize_ident(input, 0), ("".to_string(), 0))
+    }
+
+    #[test]
+    fn test_tokenize_string() {
+        let input = r#""a\tb\\\"\101\x41\0" + 1"#.as_bytes();
+        assert_eq!(Token::tokenize_string(input, 0), Ok((b"a\tb\\\"AA\0".to_vec(), 20)));
+
+        let input = r#""abc"#.as_bytes();
+        assert_eq!(Token::tokenize_string(input, 0), Err(TokenizeError::unclosed_string(Location(0, 3), String::from("\"abc"))));
+
+        let input = r#"'\n' 'a' '\377'"#.as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Num(10), Location(0, 3), 0),
+            Token::new(TokenKind::Num(97), Location(5, 7), 0),
+            Token::new(TokenKind::Num(-1i64 as u64), Location(9, 14), 0),
+        ]));
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let input = "<=".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![Token::new(TokenKind::EqualSmall, Location(0, 1), 0)]));
+
+        let input = "<".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![Token::new(TokenKind::Small, Location(0, 0), 0)]));
+
+        let input = "ifa".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Ident("ifa".to_string()), Location(0, 2), 0)
+        ]));
+
+        let input = "if 0123".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::If, Location(0, 1), 0),
+            Token::new(TokenKind::Num(123), Location(3, 6), 0)
+        ]));
+
+        let input = "int *p, x;".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Int, Location(0, 2), 0),
+            Token::new(TokenKind::Asterisk, Location(4, 4), 0),
+            Token::new(TokenKind::Ident("p".to_string()), Location(5, 5), 0),
+            Token::new(TokenKind::Comma, Location(6, 6), 0),
+            Token::new(TokenKind::Ident("x".to_string()), Location(8, 8), 0),
+            Token::new(TokenKind::SemiColon, Location(9, 9), 0),
+        ]));
+
+        let input = "a[10]".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Ident("a".to_string()), Location(0, 0), 0),
+            Token::new(TokenKind::LSquareBra, Location(1, 1), 0),
+            Token::new(TokenKind::Num(10), Location(2, 3), 0),
+            Token::new(TokenKind::RSquareBra, Location(4, 4), 0),
+        ]));
+
+        let input = "char *s".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Char, Location(0, 3), 0),
+            Token::new(TokenKind::Asterisk, Location(5, 5), 0),
+            Token::new(TokenKind::Ident("s".to_string()), Location(6, 6), 0),
+        ]));
+
+        let input = "s.a->b - 1".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Ident("s".to_string()), Location(0, 0), 0),
+            Token::new(TokenKind::Dot, Location(1, 1), 0),
+            Token::new(TokenKind::Ident("a".to_string()), Location(2, 2), 0),
+            Token::new(TokenKind::Arrow, Location(3, 4), 0),
+            Token::new(TokenKind::Ident("b".to_string()), Location(5, 5), 0),
+            Token::new(TokenKind::Minus, Location(7, 7), 0),
+            Token::new(TokenKind::Num(1), Location(9, 9), 0),
+        ]));
+
+        let input = "(char *, ...)".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::LParen, Location(0, 0), 0),
+            Token::new(TokenKind::Char, Location(1, 4), 0),
+            Token::new(TokenKind::Asterisk, Location(6, 6), 0),
+            Token::new(TokenKind::Comma, Location(7, 7), 0),
+            Token::new(TokenKind::Ellipsis, Location(9, 11), 0),
+            Token::new(TokenKind::RParen, Location(12, 12), 0),
+        ]));
+
+        let input = "a @ b".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Err(TokenizeError::invalid_char(Location(2, 2), String::from("a @ b"))));
+
+        let input = "sizeof sizeofa".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Sizeof, Location(0, 5), 0),
+            Token::new(TokenKind::Ident("sizeofa".to_string()), Location(7, 13), 0),
+        ]));
+
+        let input = "typedef int T;".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Typedef, Location(0, 6), 0),
+            Token::new(TokenKind::Int, Location(8, 10), 0),
+            Token::new(TokenKind::Ident("T".to_string()), Location(12, 12), 0),
+            Token::new(TokenKind::SemiColon, Location(13, 13), 0),
+        ]));
+
+        let input = "static extern externa".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Static, Location(0, 5), 0),
+            Token::new(TokenKind::Extern, Location(7, 12), 0),
+            Token::new(TokenKind::Ident("externa".to_string()), Location(14, 20), 0),
+        ]));
+
+        let input = "const char *volatile p;".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Const, Location(0, 4), 0),
+            Token::new(TokenKind::Char, Location(6, 9), 0),
+            Token::new(TokenKind::Asterisk, Location(11, 11), 0),
+            Token::new(TokenKind::Volatile, Location(12, 19), 0),
+            Token::new(TokenKind::Ident("p".to_string()), Location(21, 21), 0),
+            Token::new(TokenKind::SemiColon, Location(22, 22), 0),
+        ]));
+
+        let input = "a<<1>>b<=c".as_bytes();
+        assert_eq!(Token::tokenize(input, 0), Ok(vec![
+            Token::new(TokenKind::Ident("a".to_string()), Location(0, 0), 0),
+            Token::new(TokenKind::ShiftLeft, Location(1, 2), 0),
+            Token::new(TokenKind::Num(1), Location(3, 3), 0),
+            Token::new(TokenKind::ShiftRight, Location(4, 5), 0),
+            Token::new(TokenKind::Ident("b".to_string()), Location(6, 6), 0),
+            Token::new(TokenKind::EqualSmall, Location(7, 8), 0),
+            Token::new(TokenKind::Ident("c".to_string()), Location(9, 9), 0),
+        ]))
+    }
+}
@@ -0,0 +1,552 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Void,
+    Bool,
+    Char,
+    Short,
+    Int,
+    Long,
+    UChar,
+    UShort,
+    UInt,
+    ULong,
+    Float,
+    Double,
+    Enum,
+    Ptr(Box<Type>),
+    Array(Box<Type>, usize),
+    Struct(StructRef),
+    Func(Box<FuncType>),
+    Qualified(Box<Type>, Qualifiers),
+}
+
+// const, volatileの修飾
+// 配列への修飾は要素の型に付ける
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Qualifiers {
+    pub is_const: bool,
+    pub is_volatile: bool,
+}
+
+impl Qualifiers {
+    // selfの修飾を全てotherが持っているか
+    pub fn is_subset_of(&self, other: &Qualifiers) -> bool {
+        (!self.is_const || other.is_const) && (!self.is_volatile || other.is_volatile)
+    }
+}
+
+// SysV x86-64で引数, 返り値を渡すときの8byteごとの分類
+// INTEGERは汎用レジスタ, SSEはxmmレジスタで渡す
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgClass {
+    Integer,
+    Sse,
+}
+
+// paramsがNoneの関数(f()やK&R形式の定義)は呼び出し時に引数を検査しない
+// 可変長引数(...)の部分は検査しない
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncType {
+    pub ret: Type,
+    pub params: Option<Vec<Type>>,
+    pub is_variadic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub ty: Type,
+    pub offset: usize,
+}
+
+#[derive(Debug)]
+pub struct StructType {
+    pub tag: Option<String>,
+    pub is_union: bool,
+    pub members: Vec<Member>,
+    pub size: usize,
+    pub align: usize,
+    pub is_complete: bool,  // メンバが定義済みか
+}
+
+// struct node { struct node *next; }のように自身を参照できるよう
+// 定義(メンバ)は後から埋める
+// unionもメンバの配置以外はstructと同じように扱う
+#[derive(Clone)]
+pub struct StructRef(Rc<RefCell<StructType>>);
+
+impl StructRef {
+    pub fn new(tag: Option<String>, is_union: bool) -> Self {
+        StructRef(Rc::new(RefCell::new(StructType {
+            tag,
+            is_union,
+            members: Vec::new(),
+            size: 0,
+            align: 1,
+            is_complete: false,
+        })))
+    }
+
+    // SysV x86-64のレイアウト
+    // 各メンバはその型のalignmentの倍数のoffsetに置き、
+    // 全体の大きさは最大のalignmentの倍数に切り上げる
+    // unionは全てのメンバをoffset 0に置き、大きさは最大のメンバに合わせる
+    pub fn define(&self, members: Vec<(String, Type)>) {
+        let is_union = self.0.borrow().is_union;
+        let mut offset: usize = 0;
+        let mut size: usize = 0;
+        let mut align: usize = 1;
+        let mut res_members = Vec::new();
+        for (name, ty) in members.into_iter() {
+            if !is_union {
+                offset = offset.div_ceil(ty.align()) * ty.align();
+            }
+            align = align.max(ty.align());
+            size = size.max(offset + ty.size());
+            res_members.push(Member { name, ty, offset });
+            if !is_union {
+                offset = size;
+            }
+        }
+        let mut struct_type = self.0.borrow_mut();
+        struct_type.members = res_members;
+        struct_type.size = size.div_ceil(align) * align;
+        struct_type.align = align;
+        struct_type.is_complete = true;
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.0.borrow().is_complete
+    }
+
+    pub fn member(&self, name: &str) -> Option<Member> {
+        self.0.borrow().members.iter().find(|member| member.name == name).cloned()
+    }
+
+    pub fn members(&self) -> Vec<Member> {
+        self.0.borrow().members.clone()
+    }
+}
+
+// 自己参照するため中身ではなく同じ定義かどうかで比較する
+impl PartialEq for StructRef {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for StructRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let struct_type = self.0.borrow();
+        let kind = if struct_type.is_union { "union" } else { "struct" };
+        match &struct_type.tag {
+            Some(tag) => write!(f, "{} {}", kind, tag),
+            None => write!(f, "{} <anonymous>", kind),
+        }
+    }
+}
+
+impl Type {
+    pub fn pointer_to(ty: Type) -> Self {
+        Type::Ptr(Box::new(ty))
+    }
+
+    pub fn array_of(ty: Type, len: usize) -> Self {
+        Type::Array(Box::new(ty), len)
+    }
+
+    // 返り値と引数の型の修飾は関数の型には含めない
+    pub fn func(ret: Type, params: Option<Vec<Type>>, is_variadic: bool) -> Self {
+        let ret = ret.unqual().clone();
+        let params = params.map(|params| params.iter().map(|ty| ty.unqual().clone()).collect());
+        Type::Func(Box::new(FuncType { ret, params, is_variadic }))
+    }
+
+    pub fn qualify(self, qualifiers: Qualifiers) -> Self {
+        if qualifiers == Qualifiers::default() {
+            return self;
+        }
+        match self {
+            Type::Array(base, len) => Type::array_of(base.qualify(qualifiers), len),
+            Type::Qualified(ty, old) => Type::Qualified(ty, Qualifiers {
+                is_const: old.is_const || qualifiers.is_const,
+                is_volatile: old.is_volatile || qualifiers.is_volatile,
+            }),
+            ty => Type::Qualified(Box::new(ty), qualifiers),
+        }
+    }
+
+    // 修飾を外した型
+    pub fn unqual(&self) -> &Type {
+        match self {
+            Type::Qualified(ty, _) => ty,
+            ty => ty,
+        }
+    }
+
+    pub fn qualifiers(&self) -> Qualifiers {
+        match self {
+            Type::Qualified(_, qualifiers) => *qualifiers,
+            _ => Qualifiers::default(),
+        }
+    }
+
+    // 代入できないオブジェクトの型(constなメンバを持つ構造体を含む)
+    pub fn is_const(&self) -> bool {
+        if self.qualifiers().is_const {
+            return true;
+        }
+        match self.unqual() {
+            Type::Struct(struct_ref) => struct_ref.members().iter().any(|member| member.ty.is_const()),
+            Type::Array(base, _) => base.is_const(),
+            _ => false,
+        }
+    }
+
+    // 定義前のstruct(とその配列)は大きさが分からないため, オブジェクトの型に使えない
+    // ポインタの指す先としては使える
+    pub fn is_incomplete(&self) -> bool {
+        match self.unqual() {
+            Type::Struct(struct_ref) => !struct_ref.is_complete(),
+            Type::Array(base, _) => base.is_incomplete(),
+            _ => false,
+        }
+    }
+
+    // ポインタの変換で指す先の修飾が失われるか(const int *からint *など)
+    pub fn discards_qualifiers(&self, from: &Type) -> bool {
+        match (self.base(), from.base()) {
+            (Some(to), Some(from)) => !from.qualifiers().is_subset_of(&to.qualifiers()),
+            _ => false,
+        }
+    }
+
+    // 整数型の大きさはLP64に合わせる
+    pub fn size(&self) -> usize {
+        match self.unqual() {
+            // voidと関数はGCCに合わせて1とする
+            Type::Void | Type::Func(_) => 1,
+            Type::Bool | Type::Char | Type::UChar => 1,
+            Type::Short | Type::UShort => 2,
+            Type::Int | Type::UInt => 4,
+            Type::Long | Type::ULong => 8,
+            Type::Float => 4,
+            Type::Double => 8,
+            // enumはintとして扱う
+            Type::Enum => 4,
+            Type::Ptr(_) => 8,
+            Type::Array(base, len) => base.size() * len,
+            Type::Struct(struct_ref) => struct_ref.0.borrow().size,
+            Type::Qualified(..) => unreachable!(),
+        }
+    }
+
+    pub fn align(&self) -> usize {
+        match self.unqual() {
+            Type::Array(base, _) => base.align(),
+            Type::Struct(struct_ref) => struct_ref.0.borrow().align,
+            _ => self.size(),
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self.unqual(),
+            Type::Bool | Type::Char | Type::Short | Type::Int | Type::Long
+            | Type::UChar | Type::UShort | Type::UInt | Type::ULong | Type::Enum
+        )
+    }
+
+    pub fn is_flonum(&self) -> bool {
+        matches!(self.unqual(), Type::Float | Type::Double)
+    }
+
+    // 算術型(整数と浮動小数点数)
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_flonum()
+    }
+
+    pub fn is_unsigned(&self) -> bool {
+        matches!(self.unqual(), Type::Bool | Type::UChar | Type::UShort | Type::UInt | Type::ULong)
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self.unqual(), Type::Array(_, _))
+    }
+
+    pub fn is_struct(&self) -> bool {
+        matches!(self.unqual(), Type::Struct(_))
+    }
+
+    // ポインタの指す先の型, 配列は要素の型(修飾を含む)
+    pub fn base(&self) -> Option<&Type> {
+        match self.unqual() {
+            Type::Ptr(base) | Type::Array(base, _) => Some(base),
+            _ => None,
+        }
+    }
+
+    // 整数拡張: intより小さい整数型(とenum)はintとして計算する
+    pub fn promote(self) -> Type {
+        match self.unqual() {
+            Type::Bool | Type::Char | Type::UChar | Type::Short | Type::UShort | Type::Enum => Type::Int,
+            ty => ty.clone(),
+        }
+    }
+
+    // 通常の算術変換: 二項演算の両辺を揃える型
+    // 浮動小数点数があればdouble, floatの順に優先する
+    // 整数は大きさが違えば大きい方, 同じなら符号なしを優先する
+    // (long と unsigned int は long で全ての値を表せるため long)
+    pub fn common_type(lhs: Type, rhs: Type) -> Type {
+        if lhs == Type::Double || rhs == Type::Double {
+            return Type::Double;
+        }
+        if lhs == Type::Float || rhs == Type::Float {
+            return Type::Float;
+        }
+        let (lhs, rhs) = (lhs.promote(), rhs.promote());
+        if lhs.size() != rhs.size() {
+            return if lhs.size() > rhs.size() { lhs } else { rhs };
+        }
+        if rhs.is_unsigned() { rhs } else { lhs }
+    }
+
+    // 式中の配列は先頭要素へのポインタとして扱う
+    pub fn decay(self) -> Type {
+        match self {
+            Type::Array(base, _) => Type::Ptr(base),
+            ty => ty,
+        }
+    }
+
+    // 型の中のfromをtoに置き換える
+    // int (*fp)(int)のように外側の型が後から決まる宣言子で仮の型を置き換えるのに使う
+    pub fn replace(self, from: &Type, to: &Type) -> Type {
+        if self == *from {
+            return to.clone();
+        }
+        match self {
+            Type::Ptr(base) => Type::Ptr(Box::new(base.replace(from, to))),
+            Type::Array(base, len) => Type::Array(Box::new(base.replace(from, to)), len),
+            Type::Func(func_ty) => {
+                let FuncType { ret, params, is_variadic } = *func_ty;
+                Type::func(ret.replace(from, to), params, is_variadic)
+            },
+            Type::Qualified(ty, qualifiers) => ty.replace(from, to).qualify(qualifiers),
+            ty => ty,
+        }
+    }
+
+    // 8byteごとの分類, 16byteを超える構造体はメモリで渡すためNone
+    // 構造体の8byteは中のメンバが全て浮動小数点数ならSSE, それ以外はINTEGER
+    pub fn classify(&self) -> Option<Vec<ArgClass>> {
+        if !self.is_struct() {
+            let class = if self.is_flonum() { ArgClass::Sse } else { ArgClass::Integer };
+            return Some(vec![class]);
+        }
+        if self.size() > 16 {
+            return None;
+        }
+        let classes = (0..self.size().div_ceil(8))
+            .map(|i| match self.is_flonum_only(i * 8, i * 8 + 8, 0) {
+                true => ArgClass::Sse,
+                false => ArgClass::Integer,
+            })
+            .collect();
+        Some(classes)
+    }
+
+    // [lo, hi)の範囲に重なるメンバが全て浮動小数点数か(offsetは自身の先頭位置)
+    fn is_flonum_only(&self, lo: usize, hi: usize, offset: usize) -> bool {
+        match self.unqual() {
+            Type::Struct(struct_ref) => struct_ref.members().iter()
+                .all(|member| member.ty.is_flonum_only(lo, hi, offset + member.offset)),
+            Type::Array(base, len) => (0..*len)
+                .all(|i| base.is_flonum_only(lo, hi, offset + base.size() * i)),
+            _ => offset < lo || hi <= offset || self.is_flonum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_base() {
+        let ty = Type::pointer_to(Type::pointer_to(Type::Int));
+        assert_eq!(ty.base(), Some(&Type::pointer_to(Type::Int)));
+        assert_eq!(ty.base().unwrap().base(), Some(&Type::Int));
+        assert_eq!(Type::Int.base(), None);
+
+        let ty = Type::array_of(Type::Int, 10);
+        assert_eq!(ty.size(), 40);
+        assert_eq!(ty.base(), Some(&Type::Int));
+        assert_eq!(ty.decay(), Type::pointer_to(Type::Int));
+
+        let ty = Type::array_of(Type::Char, 5);
+        assert_eq!(ty.size(), 5);
+        assert_eq!(ty.align(), 1);
+
+        let ty = Type::array_of(Type::array_of(Type::Long, 4), 3);
+        assert_eq!(ty.size(), 96);
+        assert_eq!(ty.base().unwrap().size(), 32);
+    }
+
+    #[test]
+    fn test_integer_size() {
+        assert_eq!(Type::Char.size(), 1);
+        assert_eq!(Type::Short.size(), 2);
+        assert_eq!(Type::Int.size(), 4);
+        assert_eq!(Type::Long.size(), 8);
+        assert_eq!(Type::Enum.size(), 4);
+        assert_eq!(Type::Short.align(), 2);
+        assert_eq!(Type::UInt.size(), 4);
+        assert!(Type::UChar.is_unsigned());
+        assert!(!Type::Long.is_unsigned());
+    }
+
+    #[test]
+    fn test_common_type() {
+        assert_eq!(Type::Char.promote(), Type::Int);
+        assert_eq!(Type::Bool.promote(), Type::Int);
+        assert_eq!(Type::UShort.promote(), Type::Int);
+        assert_eq!(Type::UInt.promote(), Type::UInt);
+        assert_eq!(Type::common_type(Type::Char, Type::Char), Type::Int);
+        assert_eq!(Type::common_type(Type::Int, Type::UInt), Type::UInt);
+        assert_eq!(Type::common_type(Type::UInt, Type::Long), Type::Long);
+        assert_eq!(Type::common_type(Type::Long, Type::ULong), Type::ULong);
+        assert_eq!(Type::common_type(Type::UChar, Type::Long), Type::Long);
+        assert_eq!(Type::common_type(Type::ULong, Type::Float), Type::Float);
+        assert_eq!(Type::common_type(Type::Float, Type::Double), Type::Double);
+        assert_eq!(Type::common_type(Type::Char, Type::Double), Type::Double);
+    }
+
+    #[test]
+    fn test_struct_layout() {
+        let struct_ref = StructRef::new(Some("s".to_string()), false);
+        struct_ref.define(vec![
+            ("a".to_string(), Type::Char),
+            ("b".to_string(), Type::Long),
+            ("c".to_string(), Type::array_of(Type::Char, 3)),
+            ("d".to_string(), Type::Short),
+            ("e".to_string(), Type::Int),
+        ]);
+        let ty = Type::Struct(struct_ref.clone());
+        assert_eq!(struct_ref.member("b").unwrap().offset, 8);
+        assert_eq!(struct_ref.member("c").unwrap().offset, 16);
+        assert_eq!(struct_ref.member("d").unwrap().offset, 20);
+        assert_eq!(struct_ref.member("e").unwrap().offset, 24);
+        assert_eq!(struct_ref.member("f"), None);
+        assert_eq!(ty.size(), 32);
+        assert_eq!(ty.align(), 8);
+
+        // 自身へのポインタを持つstruct
+        let node = StructRef::new(Some("node".to_string()), false);
+        node.define(vec![
+            ("val".to_string(), Type::Char),
+            ("next".to_string(), Type::pointer_to(Type::Struct(node.clone()))),
+        ]);
+        assert_eq!(Type::Struct(node.clone()).size(), 16);
+        assert_eq!(node.member("next").unwrap().ty.base(), Some(&Type::Struct(node)));
+    }
+
+    #[test]
+    fn test_union_layout() {
+        let union_ref = StructRef::new(Some("u".to_string()), true);
+        union_ref.define(vec![
+            ("c".to_string(), Type::array_of(Type::Char, 9)),
+            ("n".to_string(), Type::Long),
+        ]);
+        assert_eq!(union_ref.member("c").unwrap().offset, 0);
+        assert_eq!(union_ref.member("n").unwrap().offset, 0);
+        assert_eq!(Type::Struct(union_ref.clone()).size(), 16);
+        assert_eq!(Type::Struct(union_ref).align(), 8);
+    }
+
+    #[test]
+    fn test_incomplete_struct() {
+        // struct S;の時点では不完全型で, 定義後に完全型になる
+        let struct_ref = StructRef::new(Some("S".to_string()), false);
+        let ty = Type::Struct(struct_ref.clone());
+        assert!(ty.is_incomplete());
+        assert!(Type::array_of(ty.clone(), 2).is_incomplete());
+        assert!(!Type::pointer_to(ty.clone()).is_incomplete());
+        struct_ref.define(vec![("a".to_string(), Type::Int)]);
+        assert!(!ty.is_incomplete());
+        assert!(!Type::array_of(ty, 2).is_incomplete());
+    }
+
+    #[test]
+    fn test_replace() {
+        // int (*fp[2])(void): 仮の型のポインタの配列を組み立ててから関数型に置き換える
+        let placeholder = Type::Struct(StructRef::new(None, false));
+        let inner = Type::array_of(Type::pointer_to(placeholder.clone()), 2);
+        let func = Type::func(Type::Int, Some(Vec::new()), false);
+        let ty = inner.replace(&placeholder, &func);
+        assert_eq!(ty, Type::array_of(Type::pointer_to(func), 2));
+        assert_eq!(ty.size(), 16);
+    }
+
+    #[test]
+    fn test_classify() {
+        use ArgClass::*;
+        assert_eq!(Type::Int.classify(), Some(vec![Integer]));
+        assert_eq!(Type::Double.classify(), Some(vec![Sse]));
+
+        // struct { float x, y; int n; } -> SSE, INTEGER
+        let mixed = StructRef::new(None, false);
+        mixed.define(vec![
+            ("x".to_string(), Type::Float),
+            ("y".to_string(), Type::Float),
+            ("n".to_string(), Type::Int),
+        ]);
+        assert_eq!(Type::Struct(mixed).classify(), Some(vec![Sse, Integer]));
+
+        // struct { char c; double d[1]; } -> INTEGER, SSE
+        let nested = StructRef::new(None, false);
+        nested.define(vec![
+            ("c".to_string(), Type::Char),
+            ("d".to_string(), Type::array_of(Type::Double, 1)),
+        ]);
+        assert_eq!(Type::Struct(nested).classify(), Some(vec![Integer, Sse]));
+
+        // 16byteを超える構造体はメモリ渡し
+        let large = StructRef::new(None, false);
+        large.define(vec![("a".to_string(), Type::array_of(Type::Long, 3))]);
+        assert_eq!(Type::Struct(large).classify(), None);
+    }
+
+    #[test]
+    fn test_qualifiers() {
+        let c = Qualifiers { is_const: true, is_volatile: false };
+        let v = Qualifiers { is_const: false, is_volatile: true };
+        let cv = Type::Int.qualify(c).qualify(v);
+        assert_eq!(cv.qualifiers(), Qualifiers { is_const: true, is_volatile: true });
+        assert_eq!(cv.unqual(), &Type::Int);
+        assert_eq!(cv.size(), 4);
+        assert!(cv.is_integer());
+
+        // 配列への修飾は要素に付く
+        let array = Type::array_of(Type::Int, 2).qualify(c);
+        assert_eq!(array, Type::array_of(Type::Int.qualify(c), 2));
+        assert!(array.is_const());
+
+        // constなメンバを持つ構造体は代入できない
+        let pair = StructRef::new(None, false);
+        pair.define(vec![("key".to_string(), Type::Int.qualify(c)), ("val".to_string(), Type::Int)]);
+        assert!(Type::Struct(pair).is_const());
+
+        // const int * -> int * は修飾が失われる
+        let to_const = Type::pointer_to(Type::Int.qualify(c));
+        let to_int = Type::pointer_to(Type::Int);
+        assert!(to_int.discards_qualifiers(&to_const));
+        assert!(!to_const.discards_qualifiers(&to_int));
+        assert!(!to_int.discards_qualifiers(&Type::Int));
+    }
+}
@@ -16,6 +16,17 @@ assert() {
     fi
 }
 
+assert_error() {
+    input="$1"
+
+    if ./target/debug/c99 "$input" > tmp.s 2> /dev/null; then
+        echo "#input => compile error expected"
+        exit 1
+    else
+        echo "$input => error"
+    fi
+}
+
 assert "./test/test1.txt" 3
 assert "./test/test2.txt" 18
 assert "./test/test3.txt" 3
@@ -59,5 +70,120 @@ assert "./test/test34.txt" 2
 assert "./test/test35.txt" 6
 assert "./test/test36.txt" 10
 assert "./test/test37.txt" 48
+assert "./test/test38.txt" 3
+assert "./test/test39.txt" 10
+
+assert "./test/test40.txt" 7
+assert_error "./test/test41.txt"
+
+assert "./test/test42.txt" 17
+assert_error "./test/test43.txt"
+assert_error "./test/test44.txt"
+
+assert "./test/test45.txt" 29
+
+assert "./test/test46.txt" 100
+assert "./test/test47.txt" 30
+assert_error "./test/test48.txt"
+
+assert "./test/test49.txt" 86
+assert "./test/test50.txt" 124
+assert "./test/test51.txt" 72
+
+assert "./test/test52.txt" 154
+assert "./test/test53.txt" 71
+assert_error "./test/test54.txt"
+
+assert "./test/test55.txt" 72
+assert "./test/test56.txt" 11
+
+assert "./test/test57.txt" 14
+assert "./test/test58.txt" 122
+
+assert "./test/test59.txt" 56
+assert "./test/test60.txt" 36
+assert "./test/test61.txt" 64
+assert_error "./test/test62.txt"
+assert_error "./test/test63.txt"
+
+assert "./test/test64.txt" 21
+
+assert "./test/test65.txt" 30
+assert_error "./test/test66.txt"
+
+assert "./test/test67.txt" 43
+assert "./test/test68.txt" 38
+assert_error "./test/test69.txt"
+
+assert "./test/test70.txt" 80
+assert_error "./test/test71.txt"
+
+assert "./test/test72.txt" 44
+assert_error "./test/test73.txt"
+
+assert "./test/test74.txt" 39
+
+assert "./test/test75.txt" 22
+
+assert "./test/test76.txt" 15
+
+assert "./test/test77.txt" 48
+
+assert "./test/test78.txt" 70
+assert_error "./test/test79.txt"
+assert_error "./test/test80.txt"
+
+assert "./test/test81.txt" 56
+
+assert "./test/test82.txt" 63
+
+assert "./test/test83.txt" 104
+assert_error "./test/test84.txt"
+
+assert "./test/test85.txt" 81
+assert_error "./test/test86.txt"
+
+assert "./test/test87.txt" 53
+assert_error "./test/test88.txt"
+
+assert "./test/test89.txt" 51
+assert_error "./test/test90.txt"
+assert_error "./test/test91.txt"
+assert_error "./test/test92.txt"
+
+assert_error "./test/test93.txt"
+assert_error "./test/test94.txt"
+
+assert "./test/test95.txt" 7
+assert_error "./test/test96.txt"
+
+assert "./test/test97.txt" 44
+
+assert_error "./test/test98.txt"
+
+assert "./test/test99.txt" 11
+assert_error "./test/test100.txt"
+
+assert_error "./test/test101.txt"
+
+assert_error "./test/test102.txt"
+assert_error "./test/test103.txt"
+assert_error "./test/test104.txt"
+assert "./test/test105.txt" 5
+
+assert_error "./test/test106.txt"
+assert "./test/test107.txt" 1
+
+assert "./test/test108.txt" 9
+
+assert_error "./test/test109.txt"
+assert_error "./test/test110.txt"
+assert_error "./test/test111.txt"
+assert "./test/test112.txt" 7
+
+assert_error "./test/test113.txt"
+
+assert_error "./test/test114.txt"
+assert "./test/test115.txt" 4
 
 echo OK